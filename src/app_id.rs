@@ -0,0 +1,66 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+/// User-defined application-specific file type tag
+///
+/// Is either 4 or 8 bytes long.
+///
+/// It can be stored in the trailer (see [`crate::Writer::use_app_id`]),
+/// and in a leading magic block at the start of the file (see [`crate::Writer::use_leading_magic`]).
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+pub struct AppId {
+    bytes: [u8; 8],
+    len: u8,
+}
+
+impl AppId {
+    /// Returns the tag bytes.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes.get(..self.len as usize).unwrap_or_default()
+    }
+
+    pub(crate) fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if let Ok(bytes) = <[u8; 4]>::try_from(bytes) {
+            Some(bytes.into())
+        } else if let Ok(bytes) = <[u8; 8]>::try_from(bytes) {
+            Some(bytes.into())
+        } else {
+            None
+        }
+    }
+}
+
+impl From<[u8; 4]> for AppId {
+    fn from([a, b, c, d]: [u8; 4]) -> Self {
+        Self {
+            bytes: [a, b, c, d, 0, 0, 0, 0],
+            len: 4,
+        }
+    }
+}
+
+impl From<[u8; 8]> for AppId {
+    fn from(bytes: [u8; 8]) -> Self {
+        Self { bytes, len: 8 }
+    }
+}
+
+impl From<&[u8; 4]> for AppId {
+    fn from(value: &[u8; 4]) -> Self {
+        (*value).into()
+    }
+}
+
+impl From<&[u8; 8]> for AppId {
+    fn from(value: &[u8; 8]) -> Self {
+        (*value).into()
+    }
+}
+
+impl std::fmt::Debug for AppId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AppId({:?})", String::from_utf8_lossy(self.as_bytes()))
+    }
+}
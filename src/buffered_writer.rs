@@ -0,0 +1,181 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use std::io::{IoSlice, Seek, SeekFrom, Write};
+
+/// Writer that (optionally) buffers writes, and keeps track of the stream position,
+/// so it does not need to be queried from the inner writer
+pub struct BufferedWriter<W: Write + Seek> {
+    inner: W,
+    buf: Vec<u8>,
+    capacity: usize,
+
+    /// Stream position, including buffered data, if known
+    pos: Option<u64>,
+}
+
+impl<W: Write + Seek> BufferedWriter<W> {
+    /// Creates a writer without a buffer.
+    pub fn new(writer: W) -> Self {
+        Self {
+            inner: writer,
+            buf: Vec::new(),
+            capacity: 0,
+            pos: None,
+        }
+    }
+
+    /// Sets the buffer capacity, 0 disables buffering.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    ///
+    /// Buffered data is not flushed, and the stream position is queried again afterwards,
+    /// in case the inner writer is moved.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.pos = None;
+        &mut self.inner
+    }
+
+    /// Queries the stream position from the inner writer again,
+    /// e.g. after it was moved through another handle of the same file.
+    pub fn invalidate_position(&mut self) {
+        self.pos = None;
+    }
+
+    /// Returns the inner writer, which must have been flushed.
+    pub fn into_inner(self) -> W {
+        debug_assert!(self.buf.is_empty(), "buffer should be flushed");
+        self.inner
+    }
+
+    fn flush_buf(&mut self) -> std::io::Result<()> {
+        if !self.buf.is_empty() {
+            self.inner.write_all(&self.buf)?;
+            self.buf.clear();
+        }
+        Ok(())
+    }
+
+    fn advance(&mut self, n: usize) {
+        if let Some(pos) = &mut self.pos {
+            *pos += n as u64;
+        }
+    }
+}
+
+impl<W: Write + Seek> Write for BufferedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.buf.len() + buf.len() > self.capacity {
+            self.flush_buf()?;
+        }
+
+        let n = if buf.len() >= self.capacity {
+            self.inner.write(buf)?
+        } else {
+            self.buf.extend_from_slice(buf);
+            buf.len()
+        };
+
+        self.advance(n);
+
+        Ok(n)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+        let len = bufs.iter().map(|buf| buf.len()).sum::<usize>();
+
+        if self.buf.len() + len > self.capacity {
+            self.flush_buf()?;
+        }
+
+        let n = if len >= self.capacity {
+            self.inner.write_vectored(bufs)?
+        } else {
+            for buf in bufs {
+                self.buf.extend_from_slice(buf);
+            }
+            len
+        };
+
+        self.advance(n);
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flush_buf()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write + Seek> Seek for BufferedWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        if let (SeekFrom::Start(pos), Some(current)) = (pos, self.pos) {
+            if pos == current {
+                return Ok(pos);
+            }
+        }
+
+        self.flush_buf()?;
+
+        let pos = self.inner.seek(pos)?;
+        self.pos = Some(pos);
+
+        Ok(pos)
+    }
+
+    fn stream_position(&mut self) -> std::io::Result<u64> {
+        if let Some(pos) = self.pos {
+            return Ok(pos);
+        }
+
+        let pos = self.inner.stream_position()? + self.buf.len() as u64;
+        self.pos = Some(pos);
+
+        Ok(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use test_log::test;
+
+    #[test]
+    fn buffered_writer_tracks_position() -> std::io::Result<()> {
+        let mut writer = BufferedWriter::new(Cursor::new(vec![]));
+        writer.set_capacity(8);
+
+        writer.write_all(b"abc")?;
+        assert_eq!(3, writer.stream_position()?);
+        assert!(writer.get_mut().get_ref().is_empty());
+
+        assert_eq!(
+            5,
+            writer.write_vectored(&[IoSlice::new(b"de"), IoSlice::new(b"fgh")])?
+        );
+        assert_eq!(8, writer.stream_position()?);
+        assert!(writer.get_mut().get_ref().is_empty());
+
+        // NOTE: Larger writes bypass the buffer
+        writer.write_all(b"0123456789")?;
+        assert_eq!(18, writer.stream_position()?);
+        assert_eq!(b"abcdefgh0123456789", &**writer.get_mut().get_ref());
+
+        writer.write_all(b"x")?;
+        writer.seek(SeekFrom::Start(1))?;
+        writer.write_all(b"B")?;
+        assert_eq!(2, writer.stream_position()?);
+        writer.seek(SeekFrom::End(0))?;
+        writer.flush()?;
+
+        assert_eq!(b"aBcdefgh0123456789x", &**writer.into_inner().get_ref());
+
+        Ok(())
+    }
+}
@@ -2,6 +2,8 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
+use crate::ErrorContext;
+
 /// An 128-bit checksum
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Checksum(u128);
@@ -17,13 +19,14 @@ impl Checksum {
         self.0
     }
 
-    pub(crate) fn check(&self, expected: Self) -> crate::Result<()> {
+    pub(crate) fn check(&self, expected: Self, context: ErrorContext) -> crate::Result<()> {
         if self == &expected {
             Ok(())
         } else {
             Err(crate::Error::ChecksumMismatch {
                 expected,
                 got: *self,
+                context,
             })
         }
     }
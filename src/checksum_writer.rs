@@ -3,6 +3,7 @@ use crate::Checksum;
 pub struct ChecksummedWriter<W: std::io::Write> {
     inner: W,
     hasher: xxhash_rust::xxh3::Xxh3Default,
+    len: u64,
 }
 
 impl<W: std::io::Write> ChecksummedWriter<W> {
@@ -10,9 +11,20 @@ impl<W: std::io::Write> ChecksummedWriter<W> {
         Self {
             inner: writer,
             hasher: xxhash_rust::xxh3::Xxh3Default::new(),
+            len: 0,
         }
     }
 
+    /// Returns the amount of bytes written so far.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Adds bytes to the checksum without writing them.
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.hasher.update(bytes);
+    }
+
     pub fn checksum(&self) -> Checksum {
         Checksum::from_raw(self.hasher.digest128())
     }
@@ -24,7 +36,13 @@ impl<W: std::io::Write> std::io::Write for ChecksummedWriter<W> {
     }
 
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.hasher.update(buf);
-        self.inner.write(buf)
+        let n = self.inner.write(buf)?;
+
+        #[allow(clippy::indexing_slicing)]
+        self.hasher.update(&buf[..n]);
+
+        self.len += n as u64;
+
+        Ok(n)
     }
 }
@@ -0,0 +1,138 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{toc::Toc, Checksum, Progress, TocEntry};
+use std::{
+    collections::HashMap,
+    io::{Read, Seek, SeekFrom},
+};
+
+/// Domain separation prefix, so the digest format can be changed later on
+const CONTENT_DIGEST_PREFIX: &[u8] = b"sfa content digest v1";
+
+/// An 128-bit digest of the logical contents of an archive
+///
+/// The digest covers the header, and the names and data of all sections,
+/// but not their order, their positions or any other archive metadata
+/// (e.g. the table of contents encoding).
+///
+/// Encrypted sections are hashed as stored, so they only compare equal
+/// to the exact same encrypted data.
+///
+/// Note that the digest is based on XXH3, so it identifies contents,
+/// but is not suitable to detect malicious modifications.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ContentDigest(u128);
+
+impl ContentDigest {
+    /// Converts the digest to integer.
+    #[must_use]
+    pub fn into_u128(self) -> u128 {
+        self.0
+    }
+
+    /// Combines the entries and hashes of their data.
+    pub(crate) fn compute<'a>(entries: impl Iterator<Item = (&'a TocEntry, Checksum)>) -> Self {
+        // NOTE: Sorting the (unambiguously encoded) records makes the digest independent
+        // of the section order, while keeping duplicate sections apart
+        let mut records = entries
+            .map(|(entry, hash)| {
+                let mut record = Vec::with_capacity(1 + 4 + entry.name().len() + 16);
+                record.push(u8::from(entry.is_header()));
+
+                #[allow(clippy::cast_possible_truncation)]
+                record.extend_from_slice(&(entry.name().len() as u32).to_le_bytes());
+
+                record.extend_from_slice(entry.name());
+                record.extend_from_slice(&hash.into_u128().to_le_bytes());
+                record
+            })
+            .collect::<Vec<_>>();
+
+        records.sort_unstable();
+
+        let mut hasher = xxhash_rust::xxh3::Xxh3Default::new();
+        hasher.update(CONTENT_DIGEST_PREFIX);
+
+        hasher.update(&(records.len() as u64).to_le_bytes());
+
+        for record in records {
+            hasher.update(&record);
+        }
+
+        Self(hasher.digest128())
+    }
+
+    /// Reads all section data, and computes the digest.
+    ///
+    /// Data shared by multiple sections (see [`crate::Writer::use_deduplication`]) is only read once.
+    pub(crate) fn from_reader<R: Read + Seek>(
+        reader: &mut R,
+        toc: &Toc,
+        progress: Option<&dyn Progress>,
+    ) -> crate::Result<Self> {
+        let mut entry_hashes = vec![];
+        let mut range_hashes = HashMap::new();
+        let mut buf = vec![0; 64 * 1_024];
+
+        for entry in toc.header().into_iter().chain(toc.iter()) {
+            if let Some(progress) = progress {
+                progress.on_section_start(entry.name());
+            }
+
+            if let Some(&hash) = range_hashes.get(&(entry.pos(), entry.len())) {
+                entry_hashes.push((entry, hash));
+
+                if let Some(progress) = progress {
+                    progress.on_section_end(entry);
+                }
+                continue;
+            }
+
+            reader.seek(SeekFrom::Start(entry.pos()))?;
+
+            let mut hasher = xxhash_rust::xxh3::Xxh3Default::new();
+            let mut section = reader.by_ref().take(entry.len());
+            let mut read = 0;
+
+            loop {
+                let n = section.read(&mut buf)?;
+                if n == 0 {
+                    break;
+                }
+
+                #[allow(clippy::indexing_slicing)]
+                hasher.update(&buf[..n]);
+
+                if let Some(progress) = progress {
+                    progress.on_bytes(n as u64);
+                }
+
+                read += n as u64;
+            }
+
+            if read != entry.len() {
+                return Err(crate::Error::Io(std::io::Error::from(
+                    std::io::ErrorKind::UnexpectedEof,
+                )));
+            }
+
+            let hash = Checksum::from_raw(hasher.digest128());
+            range_hashes.insert((entry.pos(), entry.len()), hash);
+            entry_hashes.push((entry, hash));
+
+            if let Some(progress) = progress {
+                progress.on_section_end(entry);
+            }
+        }
+
+        Ok(Self::compute(entry_hashes.into_iter()))
+    }
+}
+
+impl std::fmt::Display for ContentDigest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
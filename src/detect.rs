@@ -0,0 +1,189 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    trailer::writer::{TRAILER_MAGIC, TRAILER_SIZE},
+    AppId,
+};
+use std::io::{Read, Seek, SeekFrom};
+
+/// Magic bytes at the start of the leading magic block
+pub const LEADING_MAGIC: &[u8] = b"SFA";
+
+/// Version of the leading magic block layout
+const LEADING_MAGIC_VERSION: u8 = 0x1;
+
+/// Size of the leading magic block (magic, version, application ID length, application ID)
+///
+/// The application ID is padded with zero bytes to 8 bytes, so the block size is fixed.
+pub const LEADING_MAGIC_SIZE: usize = LEADING_MAGIC.len() + 1 + 1 + 8;
+
+/// Kind of archive, as detected by [`detect`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ArchiveKind {
+    /// Archive with a leading magic block (see [`crate::Writer::use_leading_magic`])
+    Tagged {
+        /// User-defined application ID
+        app_id: AppId,
+    },
+
+    /// Archive without a leading magic block
+    Untagged,
+
+    /// File with a leading magic block, but without a trailer
+    ///
+    /// This is most likely an archive that was not completely written
+    /// (see [`crate::recover`]).
+    Incomplete {
+        /// User-defined application ID
+        app_id: AppId,
+    },
+}
+
+pub fn write_leading_magic<W: std::io::Write>(mut writer: W, app_id: AppId) -> std::io::Result<()> {
+    let app_id = app_id.as_bytes();
+
+    let mut padded = [0; 8];
+    for (dst, src) in padded.iter_mut().zip(app_id) {
+        *dst = *src;
+    }
+
+    writer.write_all(LEADING_MAGIC)?;
+    writer.write_all(&[LEADING_MAGIC_VERSION])?;
+
+    #[allow(clippy::cast_possible_truncation)]
+    writer.write_all(&[app_id.len() as u8])?;
+
+    writer.write_all(&padded)?;
+    Ok(())
+}
+
+/// Reads the leading magic block from the start of the file, if it exists.
+pub fn read_leading_magic<R: Read + Seek>(reader: &mut R) -> std::io::Result<Option<AppId>> {
+    reader.seek(SeekFrom::Start(0))?;
+
+    let mut buf = [0; LEADING_MAGIC_SIZE];
+
+    match reader.read_exact(&mut buf) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+
+    let (magic, rest) = buf.split_at(LEADING_MAGIC.len());
+    let (version, rest) = rest.split_at(1);
+    let (app_id_len, app_id) = rest.split_at(1);
+
+    if magic != LEADING_MAGIC || version != [LEADING_MAGIC_VERSION] {
+        return Ok(None);
+    }
+
+    Ok(app_id_len
+        .first()
+        .and_then(|&len| app_id.get(..len.into()))
+        .and_then(AppId::from_slice))
+}
+
+fn has_trailer_magic<R: Read + Seek>(reader: &mut R) -> std::io::Result<bool> {
+    let file_size = reader.seek(SeekFrom::End(0))?;
+
+    if file_size < TRAILER_SIZE.unsigned_abs() {
+        return Ok(false);
+    }
+
+    reader.seek(SeekFrom::End(-TRAILER_SIZE))?;
+
+    let mut buf = [0; TRAILER_MAGIC.len()];
+    reader.read_exact(&mut buf)?;
+
+    Ok(buf == TRAILER_MAGIC)
+}
+
+/// Checks if the given file looks like an archive, without fully parsing it.
+///
+/// Checks both the leading magic block (if it exists) and the trailer magic.
+///
+/// Note that this does not validate the archive, so [`crate::Reader`] may still
+/// fail to read it. Also, if the first section of an archive without a leading magic block
+/// happens to start with a valid leading magic block, the archive is reported as tagged.
+///
+/// # Errors
+///
+/// Returns error, if an IO error occurred.
+pub fn detect<R: Read + Seek>(mut reader: R) -> std::io::Result<Option<ArchiveKind>> {
+    let app_id = read_leading_magic(&mut reader)?;
+    let has_trailer = has_trailer_magic(&mut reader)?;
+
+    Ok(match (app_id, has_trailer) {
+        (Some(app_id), true) => Some(ArchiveKind::Tagged { app_id }),
+        (Some(app_id), false) => Some(ArchiveKind::Incomplete { app_id }),
+        (None, true) => Some(ArchiveKind::Untagged),
+        (None, false) => None,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use test_log::test;
+
+    #[test]
+    fn detect_garbage() -> std::io::Result<()> {
+        assert_eq!(None, detect(Cursor::new(b""))?);
+        assert_eq!(None, detect(Cursor::new(b"SFA"))?);
+        assert_eq!(None, detect(Cursor::new(vec![7; 1_000]))?);
+        Ok(())
+    }
+
+    #[test]
+    fn detect_incomplete() -> std::io::Result<()> {
+        let mut bytes = vec![];
+        write_leading_magic(&mut bytes, AppId::from(*b"segment!"))?;
+        bytes.extend_from_slice(b"hello world");
+
+        assert_eq!(
+            Some(ArchiveKind::Incomplete {
+                app_id: AppId::from(*b"segment!")
+            }),
+            detect(Cursor::new(bytes))?,
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn detect_short_app_id() -> std::io::Result<()> {
+        let mut bytes = vec![];
+        write_leading_magic(&mut bytes, AppId::from(*b"SEGM"))?;
+        assert_eq!(b"SFA\x01\x04SEGM\0\0\0\0", &*bytes);
+
+        assert_eq!(
+            Some(AppId::from(*b"SEGM")),
+            read_leading_magic(&mut Cursor::new(bytes))?,
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn detect_invalid_leading_magic() -> std::io::Result<()> {
+        // NOTE: Unknown layout version
+        assert_eq!(
+            None,
+            read_leading_magic(&mut Cursor::new(b"SFA\x02\x08segment!"))?,
+        );
+
+        // NOTE: Invalid application ID lengths
+        for len in [0, 5, 9, 255] {
+            let mut bytes = b"SFA\x01".to_vec();
+            bytes.push(len);
+            bytes.extend_from_slice(b"segment!");
+            assert_eq!(None, read_leading_magic(&mut Cursor::new(bytes))?);
+        }
+
+        Ok(())
+    }
+}
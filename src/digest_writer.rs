@@ -0,0 +1,132 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::Checksum;
+use std::io::{IoSlice, Seek, Write};
+
+/// Writer that checksums (and hashes, if signing is enabled) everything
+/// written through it, so section checksums and digests can be stored without
+/// reading the sections back
+pub struct DigestingWriter<W: Write + Seek> {
+    inner: W,
+
+    checksum_hasher: xxhash_rust::xxh3::Xxh3Default,
+
+    #[cfg(feature = "signing")]
+    hasher: sha2::Sha256,
+
+    #[cfg(feature = "parity")]
+    parity: Option<crate::parity::ParityEncoder>,
+}
+
+impl<W: Write + Seek> DigestingWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            inner: writer,
+            checksum_hasher: xxhash_rust::xxh3::Xxh3Default::new(),
+
+            #[cfg(feature = "signing")]
+            hasher: <sha2::Sha256 as sha2::Digest>::new(),
+
+            #[cfg(feature = "parity")]
+            parity: None,
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Returns the checksum of everything written since the last call, and resets the hasher.
+    pub fn take_checksum(&mut self) -> Checksum {
+        let checksum = Checksum::from_raw(self.checksum_hasher.digest128());
+        self.checksum_hasher.reset();
+        checksum
+    }
+
+    /// Returns the digest of everything written since the last call, and resets the hasher.
+    #[cfg(feature = "signing")]
+    pub fn take_digest(&mut self) -> [u8; 32] {
+        sha2::Digest::finalize_reset(&mut self.hasher).into()
+    }
+
+    /// Computes parity of everything written from now on, using the given encoder.
+    #[cfg(feature = "parity")]
+    pub fn set_parity(&mut self, encoder: crate::parity::ParityEncoder) {
+        self.parity = Some(encoder);
+    }
+
+    /// Stops computing parity, and returns the encoder.
+    #[cfg(feature = "parity")]
+    pub fn take_parity(&mut self) -> Option<crate::parity::ParityEncoder> {
+        self.parity.take()
+    }
+
+    /// Resets the hashers, e.g. after writing data that is not part of any section.
+    pub fn reset(&mut self) {
+        self.checksum_hasher.reset();
+
+        #[cfg(feature = "signing")]
+        sha2::Digest::reset(&mut self.hasher);
+    }
+
+    /// Hashes written data, e.g. data that was written directly to the inner writer.
+    pub fn update(&mut self, buf: &[u8]) {
+        self.checksum_hasher.update(buf);
+
+        #[cfg(feature = "signing")]
+        sha2::Digest::update(&mut self.hasher, buf);
+
+        #[cfg(feature = "parity")]
+        if let Some(parity) = &mut self.parity {
+            parity.update(buf);
+        }
+    }
+}
+
+impl<W: Write + Seek> Write for DigestingWriter<W> {
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+
+        #[allow(clippy::indexing_slicing)]
+        self.update(&buf[..n]);
+
+        Ok(n)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+        let n = self.inner.write_vectored(bufs)?;
+
+        let mut remaining = n;
+
+        for buf in bufs {
+            let len = buf.len().min(remaining);
+
+            #[allow(clippy::indexing_slicing)]
+            self.update(&buf[..len]);
+
+            remaining -= len;
+        }
+
+        Ok(n)
+    }
+}
+
+impl<W: Write + Seek> Seek for DigestingWriter<W> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+
+    fn stream_position(&mut self) -> std::io::Result<u64> {
+        self.inner.stream_position()
+    }
+}
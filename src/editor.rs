@@ -0,0 +1,440 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    detect::{read_leading_magic, LEADING_MAGIC_SIZE},
+    toc::{
+        entry::{SectionName, TocEntry},
+        TocEncoding,
+    },
+    AppId, Reader, Writer,
+};
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// Where the content of a planned section comes from
+enum Source<'a> {
+    /// Raw copy of a section of the source archive
+    Original(TocEntry),
+
+    /// New content
+    Reader(Box<dyn Read + 'a>),
+}
+
+struct PlannedSection<'a> {
+    name: SectionName,
+    source: Source<'a>,
+}
+
+/// Rewrites an existing archive
+///
+/// Operations are queued and only applied when the new archive is written
+/// using [`ArchiveEditor::write`] or [`ArchiveEditor::write_to`].
+///
+/// Sections that are not replaced are copied as-is from the source archive.
+///
+/// Operations that refer to a section by name apply to the first section with that name.
+///
+/// ```
+/// # use sfa::{ArchiveEditor, Writer, Reader};
+/// # use std::io::Write;
+/// # let dir = tempfile::tempdir()?;
+/// # let path = dir.path().join("hello.sfa");
+/// # let mut file = std::fs::File::create(&path)?;
+/// # let mut writer = Writer::from_writer(&mut file);
+/// # writer.start("a")?;
+/// # writer.write_all(b"hello")?;
+/// # writer.start("b")?;
+/// # writer.write_all(b"world")?;
+/// # writer.finish()?;
+/// # drop(file);
+/// #
+/// let mut editor = ArchiveEditor::open(&path)?;
+/// editor.remove(b"a")?;
+/// editor.rename(b"b", "c")?;
+/// editor.insert(0, "d", &b"new section"[..])?;
+/// editor.write()?;
+///
+/// let reader = Reader::new(&path)?;
+/// let toc = reader.toc();
+/// assert_eq!(2, toc.len());
+/// assert_eq!(b"d", toc[0].name());
+/// assert_eq!(b"c", toc[1].name());
+/// #
+/// # Ok::<(), sfa::Error>(())
+/// ```
+pub struct ArchiveEditor<'a> {
+    path: PathBuf,
+
+    /// Application ID of the leading magic block in the source archive, if any
+    leading_magic: Option<AppId>,
+
+    /// Position and length of the header in the source archive, if any
+    header: Option<(u64, u64)>,
+
+    /// Application ID and version of the source archive, if any
+    app_id: Option<(AppId, u32)>,
+
+    toc_encoding: TocEncoding,
+
+    /// Whether the source archive stores section checksums
+    section_checksums: bool,
+
+    /// Whether the source archive has a backup copy of the table of contents
+    redundant_toc: bool,
+
+    /// Whether sections of the source archive share their data
+    deduplicate: bool,
+
+    /// Layout of the parity blocks of the source archive, if any
+    #[cfg(feature = "parity")]
+    parity: Option<crate::parity::ParityConfig>,
+
+    sections: Vec<PlannedSection<'a>>,
+}
+
+impl<'a> ArchiveEditor<'a> {
+    /// Opens an existing archive for editing.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the archive is invalid.
+    pub fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let reader = Reader::new(path)?;
+
+        let sections = reader
+            .toc()
+            .iter()
+            .map(|entry| PlannedSection {
+                name: entry.name().to_vec(),
+                source: Source::Original(entry.clone()),
+            })
+            .collect();
+
+        // NOTE: If any section starts at the beginning of the file, the leading magic
+        // is just section data that happens to look like it
+        let leading_magic = if reader
+            .header()
+            .into_iter()
+            .chain(reader.toc().iter())
+            .any(|entry| entry.pos() < LEADING_MAGIC_SIZE as u64)
+        {
+            None
+        } else {
+            read_leading_magic(&mut File::open(path)?)?
+        };
+
+        // NOTE: Sections only share their data if the archive was written with deduplication
+        let mut ranges = HashSet::new();
+        let deduplicate = reader
+            .toc()
+            .iter()
+            .filter(|entry| entry.len() > 0)
+            .any(|entry| !ranges.insert((entry.pos(), entry.len())));
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            leading_magic,
+            header: reader.header().map(|entry| (entry.pos(), entry.len())),
+            app_id: reader.app_id().zip(reader.app_version()),
+            toc_encoding: reader.toc_encoding,
+            section_checksums: reader.toc().iter().any(|entry| entry.checksum().is_some()),
+            redundant_toc: reader.redundant_toc,
+            deduplicate,
+
+            #[cfg(feature = "parity")]
+            parity: crate::parity::read_config(&mut File::open(path)?, reader.toc())?,
+            sections,
+        })
+    }
+
+    /// Returns the section names of the new archive, in order.
+    pub fn section_names(&self) -> impl Iterator<Item = &[u8]> {
+        self.sections.iter().map(|section| &*section.name)
+    }
+
+    fn position(&self, name: &[u8]) -> crate::Result<usize> {
+        self.sections
+            .iter()
+            .position(|section| section.name == name)
+            .ok_or_else(|| crate::Error::SectionNotFound { name: name.into() })
+    }
+
+    /// Removes a section.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if the section does not exist.
+    pub fn remove(&mut self, name: &[u8]) -> crate::Result<()> {
+        let idx = self.position(name)?;
+        self.sections.remove(idx);
+        Ok(())
+    }
+
+    /// Renames a section, keeping its content and position.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if the section does not exist, or is an encrypted section
+    /// of the source archive (whose name is authenticated, see `Writer::use_encryption`).
+    pub fn rename(&mut self, name: &[u8], new_name: impl Into<SectionName>) -> crate::Result<()> {
+        let idx = self.position(name)?;
+
+        #[allow(clippy::indexing_slicing)]
+        let section = &mut self.sections[idx];
+
+        if let Source::Original(entry) = &section.source {
+            if entry.encryption().is_some() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "encrypted sections cannot be renamed",
+                )
+                .into());
+            }
+        }
+
+        section.name = new_name.into();
+
+        Ok(())
+    }
+
+    /// Replaces the content of a section, keeping its name and position.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if the section does not exist.
+    pub fn replace(&mut self, name: &[u8], reader: impl Read + 'a) -> crate::Result<()> {
+        let idx = self.position(name)?;
+
+        #[allow(clippy::indexing_slicing)]
+        {
+            self.sections[idx].source = Source::Reader(Box::new(reader));
+        }
+
+        Ok(())
+    }
+
+    /// Inserts a new section at the given index.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if the index is out of bounds.
+    pub fn insert(
+        &mut self,
+        idx: usize,
+        name: impl Into<SectionName>,
+        reader: impl Read + 'a,
+    ) -> crate::Result<()> {
+        if idx > self.sections.len() {
+            return Err(crate::Error::IndexOutOfBounds {
+                index: idx,
+                len: self.sections.len(),
+            });
+        }
+
+        self.sections.insert(
+            idx,
+            PlannedSection {
+                name: name.into(),
+                source: Source::Reader(Box::new(reader)),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Appends a new section after all other sections.
+    pub fn push(&mut self, name: impl Into<SectionName>, reader: impl Read + 'a) {
+        self.sections.push(PlannedSection {
+            name: name.into(),
+            source: Source::Reader(Box::new(reader)),
+        });
+    }
+
+    /// Moves a section to the given index.
+    ///
+    /// The index refers to the section order after the section has been taken out.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if the section does not exist, or the index is out of bounds.
+    pub fn move_to(&mut self, name: &[u8], idx: usize) -> crate::Result<()> {
+        let from = self.position(name)?;
+
+        if idx >= self.sections.len() {
+            return Err(crate::Error::IndexOutOfBounds {
+                index: idx,
+                len: self.sections.len(),
+            });
+        }
+
+        let section = self.sections.remove(from);
+        self.sections.insert(idx, section);
+
+        Ok(())
+    }
+
+    /// Reorders the sections.
+    ///
+    /// The given sections are moved to the front, in the given order.
+    /// All other sections keep their relative order after them.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if any of the sections does not exist, in which case
+    /// the sections found before are still moved to the front.
+    pub fn reorder<I: AsRef<[u8]>>(
+        &mut self,
+        names: impl IntoIterator<Item = I>,
+    ) -> crate::Result<()> {
+        let mut front = Vec::new();
+
+        let mut result = Ok(());
+
+        for name in names {
+            match self.position(name.as_ref()) {
+                Ok(idx) => front.push(self.sections.remove(idx)),
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+
+        // NOTE: Even if a section was not found, we put all sections back
+        front.append(&mut self.sections);
+        self.sections = front;
+
+        result
+    }
+
+    /// Writes the new archive, atomically replacing the source archive.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn write(self) -> crate::Result<()> {
+        let path = self.path.clone();
+        self.write_to(path)
+    }
+
+    /// Writes the new archive to the given path, atomically replacing any existing file.
+    ///
+    /// The archive is first written into a temporary file in the same folder,
+    /// which is then renamed to the destination path.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn write_to(self, dest: impl AsRef<Path>) -> crate::Result<()> {
+        let dest = dest.as_ref();
+
+        let folder = match dest.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+
+        let file_name = dest.file_name().unwrap_or_default().to_string_lossy();
+
+        let tmp_path = folder.join(format!(".{file_name}.{}.tmp", std::process::id()));
+
+        log::trace!(
+            "Rewriting archive {} into {}",
+            self.path.display(),
+            tmp_path.display()
+        );
+
+        let result = self.write_into(&tmp_path).and_then(|()| {
+            std::fs::rename(&tmp_path, dest)?;
+
+            #[cfg(not(target_os = "windows"))]
+            File::open(folder)?.sync_all()?;
+
+            Ok(())
+        });
+
+        if result.is_err() {
+            if let Err(e) = std::fs::remove_file(&tmp_path) {
+                log::warn!(
+                    "Failed to remove temporary file {}: {e:?}",
+                    tmp_path.display()
+                );
+            }
+        }
+
+        result
+    }
+
+    fn write_into(self, tmp_path: &Path) -> crate::Result<()> {
+        let mut src = File::open(&self.path)?;
+
+        let file = File::options()
+            .write(true)
+            .create_new(true)
+            .open(tmp_path)?;
+
+        let mut writer = Writer::from_writer(file).use_write_buffer(64 * 1_024);
+
+        if let Some(app_id) = self.leading_magic {
+            writer = writer.use_leading_magic(app_id);
+        }
+
+        if let Some((app_id, version)) = self.app_id {
+            writer = writer.use_app_id(app_id, version);
+        }
+
+        if self.toc_encoding == TocEncoding::Compact {
+            writer = writer.use_compact_toc();
+        }
+
+        if self.section_checksums {
+            writer = writer.use_section_checksums();
+        }
+
+        if self.redundant_toc {
+            writer = writer.use_redundant_toc();
+        }
+
+        if self.deduplicate {
+            writer = writer.use_deduplication();
+        }
+
+        #[cfg(feature = "parity")]
+        if let Some(config) = self.parity {
+            writer = writer
+                .use_parity(config.data_shards, config.parity_shards)
+                .use_parity_block_size(config.block_size);
+        }
+
+        if let Some((pos, len)) = self.header {
+            let mut header = Vec::new();
+            src.seek(SeekFrom::Start(pos))?;
+            (&mut src).take(len).read_to_end(&mut header)?;
+            writer.write_header(&header)?;
+        }
+
+        for section in self.sections {
+            match section.source {
+                Source::Original(entry) => {
+                    // NOTE: Encrypted sections are copied without decrypting them
+                    src.seek(SeekFrom::Start(entry.pos()))?;
+                    writer.copy_raw_section(section.name, &mut src, &entry)?;
+                }
+                Source::Reader(mut reader) => {
+                    writer.write_section_from(section.name, &mut reader)?;
+                }
+            }
+        }
+
+        let file = writer.into_inner()?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,666 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    toc::entry::{EncryptionInfo, TocEntry},
+    ErrorContext,
+};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, AeadInPlace, OsRng},
+    KeyInit, XChaCha20Poly1305, XNonce,
+};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// XChaCha20-Poly1305
+const ALGORITHM_XCHACHA20_POLY1305: u8 = 0x0;
+
+/// Size of the authentication tag appended to every chunk
+const TAG_SIZE: u64 = 16;
+
+#[allow(clippy::cast_possible_truncation)]
+const TAG_SIZE_USIZE: usize = TAG_SIZE as usize;
+
+/// Default amount of plaintext bytes per chunk
+pub const DEFAULT_CHUNK_SIZE: u32 = 64 * 1_024;
+
+/// Maximum amount of plaintext bytes per chunk, which bounds the memory needed to decrypt a chunk
+pub const MAX_CHUNK_SIZE: u32 = 16 * 1_024 * 1_024;
+
+/// 256-bit key used to encrypt sections
+///
+/// The key ID is stored in every encrypted section, so the correct key can be
+/// selected when reading (e.g. per tenant, or after key rotation).
+#[derive(Clone)]
+pub struct EncryptionKey {
+    id: u32,
+    key: [u8; 32],
+}
+
+impl EncryptionKey {
+    /// Creates a new encryption key with a user-defined key ID.
+    #[must_use]
+    pub fn new(id: u32, key: [u8; 32]) -> Self {
+        Self { id, key }
+    }
+
+    /// Returns the user-defined key ID.
+    #[must_use]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(&self.key.into())
+    }
+}
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EncryptionKey({}, <redacted>)", self.id)
+    }
+}
+
+/// Nonce = random per-section prefix, chunk index and a flag marking the last chunk,
+/// so chunks cannot be reordered, and the section cannot be truncated unnoticed
+fn chunk_nonce(prefix: &[u8; 16], idx: u32, last: bool) -> XNonce {
+    let mut nonce = [0; 24];
+
+    let (head, tail) = nonce.split_at_mut(16);
+    head.copy_from_slice(prefix);
+
+    let (idx_bytes, last_bytes) = tail.split_at_mut(4);
+    idx_bytes.copy_from_slice(&idx.to_le_bytes());
+    last_bytes.copy_from_slice(&u32::from(last).to_le_bytes());
+
+    nonce.into()
+}
+
+/// Associated data = key ID, chunk size, a flag marking the last chunk, the plaintext length,
+/// nonce prefix and section name, so the encryption parameters in the table of contents
+/// cannot be modified, and sections cannot be swapped unnoticed
+///
+/// The plaintext length is only known when the last chunk is sealed, so it is 0 for all other chunks.
+fn chunk_associated_data(
+    name: &[u8],
+    key_id: u32,
+    chunk_size: u32,
+    nonce_prefix: &[u8; 16],
+    plaintext_len: Option<u64>,
+) -> Vec<u8> {
+    let mut data = Vec::with_capacity(33 + name.len());
+    data.extend_from_slice(&key_id.to_le_bytes());
+    data.extend_from_slice(&chunk_size.to_le_bytes());
+    data.push(u8::from(plaintext_len.is_some()));
+    data.extend_from_slice(&plaintext_len.unwrap_or_default().to_le_bytes());
+    data.extend_from_slice(nonce_prefix);
+    data.extend_from_slice(name);
+    data
+}
+
+/// Encrypts a section while it is being written
+pub struct SectionEncryptor {
+    cipher: XChaCha20Poly1305,
+    key_id: u32,
+    chunk_size: u32,
+    nonce_prefix: [u8; 16],
+
+    /// Name of the section, which is authenticated with every chunk
+    name: Vec<u8>,
+
+    /// Plaintext of the current chunk
+    buffer: Vec<u8>,
+
+    chunk_idx: u32,
+    plaintext_len: u64,
+}
+
+impl SectionEncryptor {
+    pub fn new(key: &EncryptionKey, chunk_size: u32, name: &[u8]) -> Self {
+        let mut nonce_prefix = [0; 16];
+        OsRng.fill_bytes(&mut nonce_prefix);
+
+        Self {
+            cipher: key.cipher(),
+            key_id: key.id,
+            chunk_size,
+            nonce_prefix,
+            name: name.to_vec(),
+            buffer: Vec::with_capacity(chunk_size as usize + TAG_SIZE_USIZE),
+            chunk_idx: 0,
+            plaintext_len: 0,
+        }
+    }
+
+    /// Returns the amount of plaintext bytes written so far.
+    pub fn plaintext_len(&self) -> u64 {
+        self.plaintext_len
+    }
+
+    fn seal_chunk<W: Write>(&mut self, writer: &mut W, last: bool) -> std::io::Result<()> {
+        let nonce = chunk_nonce(&self.nonce_prefix, self.chunk_idx, last);
+        let associated_data = chunk_associated_data(
+            &self.name,
+            self.key_id,
+            self.chunk_size,
+            &self.nonce_prefix,
+            last.then_some(self.plaintext_len),
+        );
+
+        self.cipher
+            .encrypt_in_place(&nonce, &associated_data, &mut self.buffer)
+            .map_err(|_| std::io::Error::other("failed to encrypt chunk"))?;
+
+        writer.write_all(&self.buffer)?;
+        self.buffer.clear();
+
+        self.chunk_idx = self
+            .chunk_idx
+            .checked_add(1)
+            .ok_or_else(|| std::io::Error::other("too many chunks in encrypted section"))?;
+
+        Ok(())
+    }
+
+    pub fn write<W: Write>(&mut self, writer: &mut W, mut buf: &[u8]) -> std::io::Result<()> {
+        self.plaintext_len += buf.len() as u64;
+
+        while !buf.is_empty() {
+            // NOTE: Only seal a full chunk once we know more data follows,
+            // because the last chunk is sealed differently
+            if self.buffer.len() == self.chunk_size as usize {
+                self.seal_chunk(writer, false)?;
+            }
+
+            let n = (self.chunk_size as usize - self.buffer.len()).min(buf.len());
+            let (head, tail) = buf.split_at(n);
+            self.buffer.extend_from_slice(head);
+            buf = tail;
+        }
+
+        Ok(())
+    }
+
+    pub fn finish<W: Write>(mut self, writer: &mut W) -> std::io::Result<EncryptionInfo> {
+        self.seal_chunk(writer, true)?;
+
+        Ok(EncryptionInfo {
+            algorithm: ALGORITHM_XCHACHA20_POLY1305,
+            key_id: self.key_id,
+            chunk_size: self.chunk_size,
+            nonce_prefix: self.nonce_prefix,
+            plaintext_len: self.plaintext_len,
+        })
+    }
+}
+
+/// Reader that decrypts and authenticates an encrypted section
+///
+/// Supports random access by seeking, which only decrypts the affected chunks.
+///
+/// Reads fail with [`std::io::ErrorKind::InvalidData`] if a chunk cannot be authenticated.
+pub struct DecryptingReader<R: Read + Seek> {
+    inner: R,
+    cipher: XChaCha20Poly1305,
+    info: EncryptionInfo,
+    name: Vec<u8>,
+    section_pos: u64,
+
+    /// Plaintext position
+    pos: u64,
+
+    /// Decrypted chunk
+    chunk: Vec<u8>,
+    chunk_idx: Option<u32>,
+}
+
+impl<R: Read + Seek> DecryptingReader<R> {
+    /// Creates a reader over an encrypted section.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if the section is not encrypted, or was encrypted with a different key.
+    pub fn new(inner: R, entry: &TocEntry, key: &EncryptionKey) -> crate::Result<Self> {
+        let context = || ErrorContext::section(entry.name(), entry.pos());
+
+        let Some(info) = entry.encryption() else {
+            return Err(crate::Error::NotEncrypted { context: context() });
+        };
+
+        if info.algorithm != ALGORITHM_XCHACHA20_POLY1305
+            || info.chunk_size == 0
+            || info.chunk_size > MAX_CHUNK_SIZE
+        {
+            return Err(crate::Error::UnsupportedEncryption {
+                algorithm: info.algorithm,
+                context: context(),
+            });
+        }
+
+        if info.key_id != key.id {
+            return Err(crate::Error::EncryptionKeyMismatch {
+                expected: info.key_id,
+                got: key.id,
+                context: context(),
+            });
+        }
+
+        // NOTE: The parameters are read from the file, so they must not overflow
+        let len = Self::chunk_count(info)
+            .checked_mul(TAG_SIZE)
+            .and_then(|tags_len| tags_len.checked_add(info.plaintext_len));
+
+        if len != Some(entry.len()) {
+            return Err(crate::Error::UnsupportedEncryption {
+                algorithm: info.algorithm,
+                context: context(),
+            });
+        }
+
+        Ok(Self {
+            inner,
+            cipher: key.cipher(),
+            info: info.clone(),
+            name: entry.name().to_vec(),
+            section_pos: entry.pos(),
+            pos: 0,
+            chunk: Vec::with_capacity(info.chunk_size as usize + TAG_SIZE_USIZE),
+            chunk_idx: None,
+        })
+    }
+
+    /// Returns the section length before encryption.
+    #[must_use]
+    pub fn plaintext_len(&self) -> u64 {
+        self.info.plaintext_len
+    }
+
+    /// NOTE: Even an empty section has one (empty) chunk
+    fn chunk_count(info: &EncryptionInfo) -> u64 {
+        info.plaintext_len.div_ceil(info.chunk_size.into()).max(1)
+    }
+
+    fn load_chunk(&mut self, idx: u32) -> std::io::Result<()> {
+        let chunk_size = u64::from(self.info.chunk_size);
+        let last = u64::from(idx) + 1 == Self::chunk_count(&self.info);
+
+        let plaintext_len = if last {
+            self.info.plaintext_len - u64::from(idx) * chunk_size
+        } else {
+            chunk_size
+        };
+
+        self.chunk_idx = None;
+
+        self.inner.seek(SeekFrom::Start(
+            self.section_pos + u64::from(idx) * (chunk_size + TAG_SIZE),
+        ))?;
+
+        #[allow(clippy::cast_possible_truncation)]
+        self.chunk.resize((plaintext_len + TAG_SIZE) as usize, 0);
+        self.inner.read_exact(&mut self.chunk)?;
+
+        let nonce = chunk_nonce(&self.info.nonce_prefix, idx, last);
+        let associated_data = chunk_associated_data(
+            &self.name,
+            self.info.key_id,
+            self.info.chunk_size,
+            &self.info.nonce_prefix,
+            last.then_some(self.info.plaintext_len),
+        );
+
+        self.cipher
+            .decrypt_in_place(&nonce, &associated_data, &mut self.chunk)
+            .map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("failed to authenticate chunk {idx} of encrypted section"),
+                )
+            })?;
+
+        self.chunk_idx = Some(idx);
+
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Read for DecryptingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if self.pos >= self.info.plaintext_len {
+            // NOTE: An empty section still consists of one (empty) chunk, whose tag must be checked
+            if self.info.plaintext_len == 0 && self.chunk_idx.is_none() {
+                self.load_chunk(0)?;
+            }
+            return Ok(0);
+        }
+
+        let chunk_size = u64::from(self.info.chunk_size);
+
+        let Ok(idx) = u32::try_from(self.pos / chunk_size) else {
+            return Err(std::io::Error::other("chunk index out of range"));
+        };
+
+        if self.chunk_idx != Some(idx) {
+            self.load_chunk(idx)?;
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let offset = (self.pos % chunk_size) as usize;
+
+        let available = self.chunk.get(offset..).unwrap_or_default();
+        let n = available.len().min(buf.len());
+
+        #[allow(clippy::indexing_slicing)]
+        buf[..n].copy_from_slice(&available[..n]);
+
+        self.pos += n as u64;
+
+        Ok(n)
+    }
+}
+
+impl<R: Read + Seek> Seek for DecryptingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.info.plaintext_len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+
+        let Some(new_pos) = new_pos else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            ));
+        };
+
+        self.pos = new_pos;
+
+        Ok(new_pos)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+    use crate::toc::entry::FLAG_ENCRYPTED;
+    use std::io::Cursor;
+    use test_log::test;
+
+    fn encrypt(data: &[u8], chunk_size: u32) -> std::io::Result<(Vec<u8>, TocEntry)> {
+        let key = EncryptionKey::new(1, [7; 32]);
+
+        let mut encrypted = vec![];
+        let mut encryptor = SectionEncryptor::new(&key, chunk_size, b"secret");
+        encryptor.write(&mut encrypted, data)?;
+        let info = encryptor.finish(&mut encrypted)?;
+
+        let entry = TocEntry {
+            name: b"secret".to_vec(),
+            pos: 0,
+            len: encrypted.len() as u64,
+            flags: FLAG_ENCRYPTED,
+            encryption: Some(info),
+            ..Default::default()
+        };
+
+        Ok((encrypted, entry))
+    }
+
+    #[test]
+    fn encryption_roundtrip() -> crate::Result<()> {
+        let key = EncryptionKey::new(1, [7; 32]);
+
+        for len in [0, 1, 15, 16, 17, 100] {
+            let data = (0..len).collect::<Vec<u8>>();
+            let (encrypted, entry) = encrypt(&data, 16)?;
+
+            let mut reader = DecryptingReader::new(Cursor::new(encrypted), &entry, &key)?;
+            let mut buf = vec![];
+            reader.read_to_end(&mut buf)?;
+            assert_eq!(data, buf);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn encryption_seek() -> crate::Result<()> {
+        let key = EncryptionKey::new(1, [7; 32]);
+
+        let data = (0..100).collect::<Vec<u8>>();
+        let (encrypted, entry) = encrypt(&data, 16)?;
+
+        let mut reader = DecryptingReader::new(Cursor::new(encrypted), &entry, &key)?;
+        let mut buf = [0; 10];
+
+        reader.seek(SeekFrom::Start(60))?;
+        reader.read_exact(&mut buf)?;
+        assert_eq!(&data[60..70], buf);
+
+        reader.seek(SeekFrom::End(-10))?;
+        reader.read_exact(&mut buf)?;
+        assert_eq!(&data[90..], buf);
+
+        reader.seek(SeekFrom::Current(-95))?;
+        reader.read_exact(&mut buf)?;
+        assert_eq!(&data[5..15], buf);
+
+        Ok(())
+    }
+
+    #[test]
+    fn encryption_tampered() -> crate::Result<()> {
+        let key = EncryptionKey::new(1, [7; 32]);
+
+        let data = (0..100).collect::<Vec<u8>>();
+        let (mut encrypted, entry) = encrypt(&data, 16)?;
+        *encrypted.get_mut(40).unwrap() ^= 1;
+
+        let mut reader = DecryptingReader::new(Cursor::new(encrypted), &entry, &key)?;
+        let mut buf = [0; 16];
+        reader.read_exact(&mut buf)?;
+
+        assert_eq!(
+            std::io::ErrorKind::InvalidData,
+            reader.read_exact(&mut buf).unwrap_err().kind()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn encryption_truncated() -> crate::Result<()> {
+        let key = EncryptionKey::new(1, [7; 32]);
+
+        let data = (0..64).collect::<Vec<u8>>();
+        let (encrypted, mut entry) = encrypt(&data, 16)?;
+
+        // Pretend the section ends after the 2nd chunk
+        entry.len = 2 * (16 + TAG_SIZE);
+        entry.encryption.as_mut().unwrap().plaintext_len = 32;
+
+        let mut reader = DecryptingReader::new(Cursor::new(encrypted), &entry, &key)?;
+        let mut buf = vec![];
+        assert_eq!(
+            std::io::ErrorKind::InvalidData,
+            reader.read_to_end(&mut buf).unwrap_err().kind()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn encryption_invalid_parameters() -> crate::Result<()> {
+        let key = EncryptionKey::new(1, [7; 32]);
+        let (encrypted, entry) = encrypt(b"hello", 16)?;
+
+        for (chunk_size, plaintext_len) in [
+            (16, u64::MAX),
+            (16, u64::MAX - 100),
+            (1, u64::MAX / 2),
+            (MAX_CHUNK_SIZE + 1, 5),
+            (u32::MAX, 5),
+        ] {
+            let mut entry = entry.clone();
+            let info = entry.encryption.as_mut().unwrap();
+            info.chunk_size = chunk_size;
+            info.plaintext_len = plaintext_len;
+
+            assert!(matches!(
+                DecryptingReader::new(Cursor::new(&encrypted), &entry, &key),
+                Err(crate::Error::UnsupportedEncryption { .. })
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn encryption_parameters_authenticated() -> crate::Result<()> {
+        let (encrypted, mut entry) = encrypt(b"hello", 16)?;
+
+        // NOTE: The same key bytes under another key ID must not decrypt the section
+        entry.encryption.as_mut().unwrap().key_id = 2;
+
+        let mut reader = DecryptingReader::new(
+            Cursor::new(&encrypted),
+            &entry,
+            &EncryptionKey::new(2, [7; 32]),
+        )?;
+        assert_eq!(
+            std::io::ErrorKind::InvalidData,
+            reader.read_to_end(&mut vec![]).unwrap_err().kind()
+        );
+
+        // NOTE: A larger chunk size does not change the layout of a short section
+        let (encrypted, mut entry) = encrypt(b"hello", 16)?;
+        entry.encryption.as_mut().unwrap().chunk_size = 32;
+
+        let mut reader = DecryptingReader::new(
+            Cursor::new(&encrypted),
+            &entry,
+            &EncryptionKey::new(1, [7; 32]),
+        )?;
+        assert_eq!(
+            std::io::ErrorKind::InvalidData,
+            reader.read_to_end(&mut vec![]).unwrap_err().kind()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn encryption_empty_tampered() -> crate::Result<()> {
+        let (mut encrypted, entry) = encrypt(b"", 16)?;
+        assert_eq!(TAG_SIZE, encrypted.len() as u64);
+
+        // NOTE: The tag of the only (empty) chunk is checked as well
+        encrypted[0] ^= 1;
+
+        let mut reader = DecryptingReader::new(
+            Cursor::new(&encrypted),
+            &entry,
+            &EncryptionKey::new(1, [7; 32]),
+        )?;
+        assert_eq!(
+            std::io::ErrorKind::InvalidData,
+            reader.read_to_end(&mut vec![]).unwrap_err().kind()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn encryption_swapped_sections() -> crate::Result<()> {
+        let key = EncryptionKey::new(1, [7; 32]);
+
+        let mut encrypted = vec![];
+        let mut entries = vec![];
+
+        for name in [b"a", b"b"] {
+            let pos = encrypted.len() as u64;
+
+            let mut encryptor = SectionEncryptor::new(&key, 16, name);
+            encryptor.write(&mut encrypted, b"hello")?;
+            let info = encryptor.finish(&mut encrypted)?;
+
+            entries.push(TocEntry {
+                name: name.to_vec(),
+                pos,
+                len: encrypted.len() as u64 - pos,
+                flags: FLAG_ENCRYPTED,
+                encryption: Some(info),
+                ..Default::default()
+            });
+        }
+
+        for entry in &entries {
+            let mut reader = DecryptingReader::new(Cursor::new(&encrypted), entry, &key)?;
+            assert_eq!(b"hello", &*{
+                let mut buf = vec![];
+                reader.read_to_end(&mut buf)?;
+                buf
+            });
+        }
+
+        // NOTE: Swapping the names of the entries (e.g. with a recomputed ToC checksum)
+        // is detected, because the name is authenticated
+        let (a, b) = entries.split_at_mut(1);
+        std::mem::swap(&mut a[0].name, &mut b[0].name);
+
+        for entry in &entries {
+            let mut reader = DecryptingReader::new(Cursor::new(&encrypted), entry, &key)?;
+            assert_eq!(
+                std::io::ErrorKind::InvalidData,
+                reader.read_to_end(&mut vec![]).unwrap_err().kind()
+            );
+        }
+
+        // NOTE: Pointing an entry at the data of another section is detected,
+        // because the nonce prefix is authenticated
+        let (a, b) = entries.split_at_mut(1);
+        std::mem::swap(&mut a[0].name, &mut b[0].name);
+        a[0].pos = b[0].pos;
+
+        let mut reader = DecryptingReader::new(Cursor::new(&encrypted), &entries[0], &key)?;
+        assert_eq!(
+            std::io::ErrorKind::InvalidData,
+            reader.read_to_end(&mut vec![]).unwrap_err().kind()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn encryption_wrong_key() -> crate::Result<()> {
+        let (encrypted, entry) = encrypt(b"hello", 16)?;
+
+        assert!(matches!(
+            DecryptingReader::new(
+                Cursor::new(&encrypted),
+                &entry,
+                &EncryptionKey::new(2, [7; 32])
+            ),
+            Err(crate::Error::EncryptionKeyMismatch {
+                expected: 1,
+                got: 2,
+                ..
+            })
+        ));
+
+        let mut reader = DecryptingReader::new(
+            Cursor::new(&encrypted),
+            &entry,
+            &EncryptionKey::new(1, [8; 32]),
+        )?;
+        assert!(reader.read_to_end(&mut vec![]).is_err());
+
+        Ok(())
+    }
+}
@@ -2,22 +2,124 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::checksum::Checksum;
+use crate::{checksum::Checksum, toc::entry::SectionName, AppId};
+use std::path::{Path, PathBuf};
+
+/// Additional information about where an error occurred
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ErrorContext {
+    pub(crate) path: Option<PathBuf>,
+    pub(crate) offset: Option<u64>,
+    pub(crate) section: Option<SectionName>,
+}
+
+impl ErrorContext {
+    pub(crate) fn at(offset: u64) -> Self {
+        Self {
+            offset: Some(offset),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn section(name: &[u8], offset: u64) -> Self {
+        Self {
+            offset: Some(offset),
+            section: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the path of the archive, if it was opened using a path.
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Returns the file offset the error occurred at, if known.
+    #[must_use]
+    pub fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+
+    /// Returns the name of the section the error occurred in, if any.
+    #[must_use]
+    pub fn section_name(&self) -> Option<&[u8]> {
+        self.section.as_deref()
+    }
+}
+
+impl std::fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(section) = &self.section {
+            write!(f, " in section {:?}", String::from_utf8_lossy(section))?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " at offset {offset}")?;
+        }
+        if let Some(path) = &self.path {
+            write!(f, " in {}", path.display())?;
+        }
+        Ok(())
+    }
+}
 
 /// Error type
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// IO error
     Io(std::io::Error),
 
-    /// Invalid header
-    InvalidHeader,
+    /// The file is too short to be an archive
+    TruncatedFile {
+        /// File size in bytes
+        file_size: u64,
+
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// Invalid trailer magic bytes, so the file is most likely not an archive
+    InvalidTrailerMagic {
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// Invalid trailer extension
+    InvalidTrailerExtension {
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// Invalid table of contents magic bytes
+    InvalidTocMagic {
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// The table of contents could not be decoded
+    InvalidToc {
+        /// Where the error occurred
+        context: ErrorContext,
+    },
 
     /// Unsupported file format version
-    InvalidVersion,
+    VersionUnsupported {
+        /// The version found in the file
+        found: u8,
+
+        /// Where the error occurred
+        context: ErrorContext,
+    },
 
     /// Unsupported checksum type
-    UnsupportedChecksumType,
+    UnsupportedChecksumType {
+        /// The checksum type found in the file
+        found: u8,
+
+        /// Where the error occurred
+        context: ErrorContext,
+    },
 
     /// Checksum mismatch
     ChecksumMismatch {
@@ -26,12 +128,290 @@ pub enum Error {
 
         /// The expected checksum as defined in the file format
         expected: Checksum,
+
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// A section points outside of the data region of the archive
+    SectionOutOfBounds {
+        /// Section position
+        pos: u64,
+
+        /// Section length in bytes
+        len: u64,
+
+        /// End of the data region (start of the table of contents)
+        data_end: u64,
+
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// Application ID mismatch
+    AppIdMismatch {
+        /// The expected application ID
+        expected: AppId,
+
+        /// The application ID found in the archive, if any
+        got: Option<AppId>,
+
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// The section is not encrypted
+    NotEncrypted {
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// Unsupported encryption algorithm or invalid encryption parameters
+    UnsupportedEncryption {
+        /// The algorithm found in the file
+        algorithm: u8,
+
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// The section was encrypted with a different key
+    EncryptionKeyMismatch {
+        /// The key ID found in the file
+        expected: u32,
+
+        /// The ID of the given key
+        got: u32,
+
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// The archive is not signed
+    MissingSignature {
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// The archive signature is invalid, or a section does not match its signed digest
+    InvalidSignature {
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// The archive does not match the write summary it was opened with
+    /// (see [`crate::Reader::from_summary_checked`])
+    SummaryMismatch {
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// A section name cannot be safely mapped to a file path (see [`crate::Reader::extract_to`])
+    UnsafeSectionName {
+        /// Why the name is unsafe
+        reason: &'static str,
+
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// The file has no complete section frames, so it cannot be recovered
+    /// (see [`crate::recover`])
+    NoSectionFrames {
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// The archive could not be repaired (see `Reader::repair`, requires the `parity` feature)
+    Unrepairable {
+        /// Why the archive could not be repaired
+        reason: &'static str,
+
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// The archive is split into volumes, so it has to be opened
+    /// using [`crate::MultiVolumeReader`]
+    MultiVolume {
+        /// Where the error occurred
+        context: ErrorContext,
+    },
+
+    /// Section does not exist
+    SectionNotFound {
+        /// The section name
+        name: SectionName,
+    },
+
+    /// Index is out of bounds
+    IndexOutOfBounds {
+        /// The given index
+        index: usize,
+
+        /// The amount of sections
+        len: usize,
     },
 }
 
+/// Matches the context of all error variants that have one,
+/// so the match is shared by [`Error::context`] and [`Error::context_mut`]
+macro_rules! error_context {
+    ($error:expr) => {
+        match $error {
+            Error::TruncatedFile { context, .. }
+            | Error::InvalidTrailerMagic { context }
+            | Error::InvalidTrailerExtension { context }
+            | Error::InvalidTocMagic { context }
+            | Error::InvalidToc { context }
+            | Error::VersionUnsupported { context, .. }
+            | Error::UnsupportedChecksumType { context, .. }
+            | Error::ChecksumMismatch { context, .. }
+            | Error::SectionOutOfBounds { context, .. }
+            | Error::AppIdMismatch { context, .. }
+            | Error::NotEncrypted { context }
+            | Error::UnsupportedEncryption { context, .. }
+            | Error::EncryptionKeyMismatch { context, .. }
+            | Error::MissingSignature { context }
+            | Error::InvalidSignature { context }
+            | Error::SummaryMismatch { context }
+            | Error::UnsafeSectionName { context, .. }
+            | Error::NoSectionFrames { context }
+            | Error::Unrepairable { context, .. }
+            | Error::MultiVolume { context } => Some(context),
+            Error::Io(_) | Error::SectionNotFound { .. } | Error::IndexOutOfBounds { .. } => None,
+        }
+    };
+}
+
+impl Error {
+    /// Returns where the error occurred, if known.
+    #[must_use]
+    pub fn context(&self) -> Option<&ErrorContext> {
+        error_context!(self)
+    }
+
+    fn context_mut(&mut self) -> Option<&mut ErrorContext> {
+        error_context!(self)
+    }
+
+    /// Maps an unexpected end of file to [`Error::TruncatedFile`],
+    /// e.g. if the table of contents claims more entries than the file holds.
+    pub(crate) fn truncated_at(self, file_size: u64, offset: u64) -> Self {
+        match self {
+            Self::Io(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                log::error!("File ends unexpectedly while reading at {offset}");
+                Self::TruncatedFile {
+                    file_size,
+                    context: ErrorContext::at(offset),
+                }
+            }
+            e => e,
+        }
+    }
+
+    /// Attaches the archive path to the error context.
+    pub(crate) fn with_path(mut self, path: &Path) -> Self {
+        if let Some(context) = self.context_mut() {
+            context.path = Some(path.into());
+        }
+        self
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "SfaError: {self:?}")
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::TruncatedFile { file_size, context } => {
+                write!(f, "file is truncated ({file_size} bytes){context}")
+            }
+            Self::InvalidTrailerMagic { context } => {
+                write!(f, "invalid trailer magic, not an archive{context}")
+            }
+            Self::InvalidTrailerExtension { context } => {
+                write!(f, "invalid trailer extension{context}")
+            }
+            Self::InvalidTocMagic { context } => {
+                write!(f, "invalid table of contents magic{context}")
+            }
+            Self::InvalidToc { context } => write!(f, "invalid table of contents{context}"),
+            Self::VersionUnsupported { found, context } => {
+                write!(f, "unsupported file format version {found:#x}{context}")
+            }
+            Self::UnsupportedChecksumType { found, context } => {
+                write!(f, "unsupported checksum type {found:#x}{context}")
+            }
+            Self::ChecksumMismatch {
+                got,
+                expected,
+                context,
+            } => write!(
+                f,
+                "checksum mismatch, expected {:#x}, got {:#x}{context}",
+                expected.into_u128(),
+                got.into_u128(),
+            ),
+            Self::SectionOutOfBounds {
+                pos,
+                len,
+                data_end,
+                context,
+            } => write!(
+                f,
+                "section (pos={pos}, len={len}) exceeds data region ending at {data_end}{context}",
+            ),
+            Self::AppIdMismatch {
+                expected,
+                got,
+                context,
+            } => match got {
+                Some(got) => write!(
+                    f,
+                    "application ID mismatch, expected {expected:?}, got {got:?}{context}",
+                ),
+                None => write!(
+                    f,
+                    "application ID mismatch, expected {expected:?}, but archive has none{context}",
+                ),
+            },
+            Self::NotEncrypted { context } => write!(f, "section is not encrypted{context}"),
+            Self::UnsupportedEncryption { algorithm, context } => write!(
+                f,
+                "unsupported encryption algorithm {algorithm:#x} or invalid parameters{context}",
+            ),
+            Self::EncryptionKeyMismatch {
+                expected,
+                got,
+                context,
+            } => write!(
+                f,
+                "encryption key mismatch, expected key ID {expected}, got {got}{context}",
+            ),
+            Self::MissingSignature { context } => write!(f, "archive is not signed{context}"),
+            Self::InvalidSignature { context } => write!(f, "invalid signature{context}"),
+            Self::SummaryMismatch { context } => {
+                write!(f, "archive does not match the write summary{context}")
+            }
+            Self::UnsafeSectionName { reason, context } => {
+                write!(f, "unsafe section name: {reason}{context}")
+            }
+            Self::NoSectionFrames { context } => {
+                write!(f, "no complete section frames found{context}")
+            }
+            Self::Unrepairable { reason, context } => {
+                write!(f, "archive cannot be repaired: {reason}{context}")
+            }
+            Self::MultiVolume { context } => {
+                write!(f, "archive is split into volumes{context}")
+            }
+            Self::SectionNotFound { name } => {
+                write!(f, "section {:?} not found", String::from_utf8_lossy(name))
+            }
+            Self::IndexOutOfBounds { index, len } => {
+                write!(f, "index {index} is out of bounds (section count: {len})")
+            }
+        }
     }
 }
 
@@ -0,0 +1,348 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    toc::{
+        dir::{validate_path_name, SEPARATOR},
+        Toc,
+    },
+    Checksum, ErrorContext, Progress, TocEntry,
+};
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// What to do if a file to extract already exists
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum OnCollision {
+    /// Fail with [`std::io::ErrorKind::AlreadyExists`]
+    #[default]
+    Error,
+
+    /// Keep the existing file, and skip the section
+    Skip,
+
+    /// Replace the existing file
+    ///
+    /// Existing directories are never replaced.
+    Overwrite,
+}
+
+/// Options for extracting an archive (see [`crate::Reader::extract_to`])
+#[derive(Clone, Debug)]
+pub struct ExtractOptions {
+    on_collision: OnCollision,
+    verify_checksums: bool,
+    restore_attributes: bool,
+
+    #[cfg(feature = "encryption")]
+    decryption_key: Option<crate::EncryptionKey>,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        Self {
+            on_collision: OnCollision::default(),
+            verify_checksums: false,
+            restore_attributes: true,
+
+            #[cfg(feature = "encryption")]
+            decryption_key: None,
+        }
+    }
+}
+
+impl ExtractOptions {
+    /// Sets what to do if a file to extract already exists (including
+    /// sections with duplicate names).
+    ///
+    /// Defaults to [`OnCollision::Error`].
+    #[must_use]
+    pub fn on_collision(mut self, on_collision: OnCollision) -> Self {
+        self.on_collision = on_collision;
+        self
+    }
+
+    /// Verifies the section checksums (see [`crate::Writer::use_section_checksums`]).
+    ///
+    /// Sections without a checksum are extracted without verification.
+    /// Encrypted sections are authenticated while decrypting them instead.
+    ///
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn verify_checksums(mut self, verify: bool) -> Self {
+        self.verify_checksums = verify;
+        self
+    }
+
+    /// Restores the file attributes (see [`crate::Writer::set_attributes`]), if recorded.
+    ///
+    /// Only the permission bits of the file mode are restored, and only on Unix.
+    ///
+    /// Defaults to `true`.
+    #[must_use]
+    pub fn restore_attributes(mut self, restore: bool) -> Self {
+        self.restore_attributes = restore;
+        self
+    }
+
+    /// Decrypts encrypted sections using the given key.
+    ///
+    /// Without a key, extracting an encrypted section fails.
+    #[cfg(feature = "encryption")]
+    #[must_use]
+    pub fn decryption_key(mut self, key: crate::EncryptionKey) -> Self {
+        self.decryption_key = Some(key);
+        self
+    }
+}
+
+fn unsafe_name(entry: &TocEntry, reason: &'static str) -> crate::Error {
+    log::error!(
+        "Refusing to extract section {:?}: {reason}",
+        String::from_utf8_lossy(entry.name())
+    );
+
+    crate::Error::UnsafeSectionName {
+        reason,
+        context: ErrorContext::section(entry.name(), entry.pos()),
+    }
+}
+
+/// Maps a section name to a relative path.
+fn relative_path(entry: &TocEntry) -> crate::Result<PathBuf> {
+    validate_path_name(entry.name()).map_err(|reason| unsafe_name(entry, reason))?;
+
+    let mut path = PathBuf::new();
+
+    for component in entry.name().split(|&b| b == SEPARATOR) {
+        #[cfg(unix)]
+        let component = <std::ffi::OsStr as std::os::unix::ffi::OsStrExt>::from_bytes(component);
+
+        #[cfg(not(unix))]
+        let component = std::str::from_utf8(component)
+            .map_err(|_| unsafe_name(entry, "name is not valid UTF-8"))?;
+
+        #[cfg(windows)]
+        if component.contains(':') {
+            return Err(unsafe_name(
+                entry,
+                "name contains a drive or stream separator",
+            ));
+        }
+
+        path.push(component);
+    }
+
+    // NOTE: Defense in depth, validation should already rule these out
+    if !path
+        .components()
+        .all(|component| matches!(component, std::path::Component::Normal(_)))
+    {
+        return Err(unsafe_name(entry, "name is not a relative path"));
+    }
+
+    Ok(path)
+}
+
+/// Creates the parent directories of a file, without following symbolic links.
+fn create_parents(dir: &Path, relative: &Path, entry: &TocEntry) -> crate::Result<()> {
+    let mut path = dir.to_path_buf();
+
+    let Some(parent) = relative.parent() else {
+        return Ok(());
+    };
+
+    for component in parent.components() {
+        path.push(component);
+
+        match std::fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                return Err(unsafe_name(entry, "path contains a symbolic link"));
+            }
+            Ok(metadata) if metadata.is_dir() => {}
+            Ok(_) => {
+                return Err(crate::Error::Io(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("{} exists, but is not a directory", path.display()),
+                )));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                std::fs::create_dir(&path)?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(())
+}
+
+/// Opens the section data for reading, decrypting it if needed.
+fn open_section<'a>(
+    src: &'a mut BufReader<File>,
+    entry: &TocEntry,
+    options: &ExtractOptions,
+) -> crate::Result<Box<dyn Read + 'a>> {
+    if entry.encryption().is_some() {
+        #[cfg(feature = "encryption")]
+        if let Some(key) = &options.decryption_key {
+            return Ok(Box::new(crate::DecryptingReader::new(src, entry, key)?));
+        }
+
+        #[cfg(not(feature = "encryption"))]
+        let _ = options;
+
+        return Err(crate::Error::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "section {:?} is encrypted, but no decryption key was given",
+                String::from_utf8_lossy(entry.name()),
+            ),
+        )));
+    }
+
+    src.seek(SeekFrom::Start(entry.pos()))?;
+    Ok(Box::new(src.take(entry.len())))
+}
+
+fn extract_section(
+    src: &mut BufReader<File>,
+    entry: &TocEntry,
+    target: &Path,
+    options: &ExtractOptions,
+    progress: Option<&dyn Progress>,
+) -> crate::Result<()> {
+    let mut file = File::options().write(true).create_new(true).open(target)?;
+
+    let expected_checksum = entry
+        .checksum()
+        .filter(|_| options.verify_checksums && entry.encryption().is_none());
+
+    let mut hasher = xxhash_rust::xxh3::Xxh3Default::new();
+    let mut reader = open_section(src, entry, options)?;
+    let mut buf = vec![0; 64 * 1_024];
+    let mut copied = 0;
+
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+
+        #[allow(clippy::indexing_slicing)]
+        let buf = &buf[..n];
+
+        if expected_checksum.is_some() {
+            hasher.update(buf);
+        }
+
+        file.write_all(buf)?;
+        copied += n as u64;
+
+        if let Some(progress) = progress {
+            progress.on_bytes(n as u64);
+        }
+    }
+
+    if entry.encryption().is_none() && copied != entry.len() {
+        return Err(crate::Error::Io(std::io::Error::from(
+            std::io::ErrorKind::UnexpectedEof,
+        )));
+    }
+
+    if let Some(expected) = expected_checksum {
+        Checksum::from_raw(hasher.digest128())
+            .check(expected, ErrorContext::section(entry.name(), entry.pos()))?;
+    }
+
+    if options.restore_attributes {
+        if let Some(attributes) = entry.attributes() {
+            if let Some(mtime) = attributes.mtime() {
+                file.set_modified(mtime)?;
+            }
+
+            #[cfg(unix)]
+            if let Some(mode) = attributes.mode() {
+                use std::os::unix::fs::PermissionsExt;
+
+                // NOTE: Never restore setuid, setgid or sticky bits from untrusted archives
+                file.set_permissions(std::fs::Permissions::from_mode(mode & 0o777))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn extract_to(
+    archive: File,
+    toc: &Toc,
+    dir: &Path,
+    options: &ExtractOptions,
+    progress: Option<&dyn Progress>,
+) -> crate::Result<usize> {
+    // NOTE: Check all names before writing anything
+    // Unnamed sections (e.g. data written before the first named section) have no path,
+    // so they are skipped like the header
+    let files = toc
+        .iter()
+        .filter(|entry| !entry.name().is_empty())
+        .map(|entry| Ok((entry, relative_path(entry)?)))
+        .collect::<crate::Result<Vec<_>>>()?;
+
+    std::fs::create_dir_all(dir)?;
+
+    let mut src = BufReader::new(archive);
+    let mut extracted = 0;
+
+    for (entry, relative) in files {
+        create_parents(dir, &relative, entry)?;
+
+        let target = dir.join(&relative);
+
+        match std::fs::symlink_metadata(&target) {
+            Ok(metadata) => match options.on_collision {
+                OnCollision::Skip => {
+                    log::debug!("Skipping existing file {}", target.display());
+                    continue;
+                }
+                OnCollision::Overwrite if !metadata.is_dir() => {
+                    // NOTE: If the target is a symbolic link, this removes the link itself
+                    std::fs::remove_file(&target)?;
+                }
+                _ => {
+                    return Err(crate::Error::Io(std::io::Error::new(
+                        std::io::ErrorKind::AlreadyExists,
+                        format!("{} already exists", target.display()),
+                    )));
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        log::trace!("Extracting {}", target.display());
+
+        if let Some(progress) = progress {
+            progress.on_section_start(entry.name());
+        }
+
+        if let Err(e) = extract_section(&mut src, entry, &target, options, progress) {
+            if let Err(e) = std::fs::remove_file(&target) {
+                log::warn!("Failed to remove {}: {e:?}", target.display());
+            }
+            return Err(e);
+        }
+
+        if let Some(progress) = progress {
+            progress.on_section_end(entry);
+        }
+
+        extracted += 1;
+    }
+
+    Ok(extracted)
+}
@@ -0,0 +1,155 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{toc::entry::SectionName, Checksum, TocEntry};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// Magic bytes at the start of a section frame
+pub const FRAME_MAGIC: &[u8] = b"SFR!";
+
+/// Data length of a frame whose section was not finished (yet)
+const UNFINISHED_LEN: u64 = u64::MAX;
+
+fn frame_checksum(name: &[u8], len: u64) -> u64 {
+    let mut hasher = xxhash_rust::xxh3::Xxh3Default::new();
+    hasher.update(FRAME_MAGIC);
+    hasher.update(&name_len(name).to_le_bytes());
+    hasher.update(name);
+    hasher.update(&len.to_le_bytes());
+    hasher.digest()
+}
+
+fn name_len(name: &[u8]) -> u16 {
+    #[allow(clippy::expect_used)]
+    u16::try_from(name.len()).expect("section name should not be longer than 65535")
+}
+
+/// Writes the frame that precedes a section, with a placeholder for the data length.
+///
+/// Returns the offset of the data length, relative to the start of the frame,
+/// so it can be filled in once the section is finished (see [`write_frame_len`]).
+pub fn write_frame_start<W: Write>(mut writer: W, name: &[u8]) -> std::io::Result<u64> {
+    writer.write_all(FRAME_MAGIC)?;
+    writer.write_u16::<LE>(name_len(name))?;
+    writer.write_all(name)?;
+    writer.write_u64::<LE>(UNFINISHED_LEN)?;
+    writer.write_u64::<LE>(0)?;
+
+    Ok((FRAME_MAGIC.len() + 2 + name.len()) as u64)
+}
+
+/// Fills in the data length (and frame checksum) of a frame.
+pub fn write_frame_len<W: Write>(mut writer: W, name: &[u8], len: u64) -> std::io::Result<()> {
+    writer.write_u64::<LE>(len)?;
+    writer.write_u64::<LE>(frame_checksum(name, len))?;
+    Ok(())
+}
+
+/// Writes the record that follows a section, consisting of the
+/// flags (and flag-dependent fields) of its entry and the checksum of its data.
+pub fn write_frame_end<W: Write>(
+    mut writer: W,
+    entry: &TocEntry,
+    checksum: Checksum,
+) -> std::io::Result<()> {
+    let mut record = Vec::new();
+
+    #[allow(clippy::expect_used)]
+    entry
+        .write_flags_into(&mut record)
+        .expect("writing into a vec should not fail");
+
+    record.write_u128::<LE>(checksum.into_u128())?;
+
+    #[allow(clippy::expect_used)]
+    writer.write_u16::<LE>(u16::try_from(record.len()).expect("frame end should be short"))?;
+    writer.write_all(&record)?;
+    writer.write_u64::<LE>(xxhash_rust::xxh3::xxh3_64(&record))?;
+
+    Ok(())
+}
+
+/// Reads the framed section at the given position.
+///
+/// Returns the entry of the section, the checksum of its data and the position after its frame,
+/// or `None` if there is no complete section (with matching checksums) at the position.
+pub fn read_frame<R: Read + Seek>(
+    reader: &mut R,
+    pos: u64,
+) -> crate::Result<Option<(TocEntry, Checksum, u64)>> {
+    match read_frame_inner(reader, pos) {
+        Err(crate::Error::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+        result => result,
+    }
+}
+
+fn read_frame_inner<R: Read + Seek>(
+    reader: &mut R,
+    pos: u64,
+) -> crate::Result<Option<(TocEntry, Checksum, u64)>> {
+    reader.seek(SeekFrom::Start(pos))?;
+
+    let mut magic = [0; FRAME_MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+
+    if magic != FRAME_MAGIC {
+        return Ok(None);
+    }
+
+    let mut name: SectionName = vec![0; reader.read_u16::<LE>()?.into()];
+    reader.read_exact(&mut name)?;
+
+    let len = reader.read_u64::<LE>()?;
+
+    if reader.read_u64::<LE>()? != frame_checksum(&name, len) {
+        log::debug!("Frame at {pos} is unfinished or corrupt");
+        return Ok(None);
+    }
+
+    let data_pos = reader.stream_position()?;
+
+    let mut hasher = xxhash_rust::xxh3::Xxh3Default::new();
+    let mut buf = vec![0; 64 * 1_024];
+    let mut remaining = len;
+
+    while remaining > 0 {
+        #[allow(clippy::cast_possible_truncation)]
+        let n = (buf.len() as u64).min(remaining) as usize;
+
+        #[allow(clippy::indexing_slicing)]
+        let chunk = &mut buf[..n];
+
+        reader.read_exact(chunk)?;
+        hasher.update(chunk);
+        remaining -= n as u64;
+    }
+
+    let mut record = vec![0; reader.read_u16::<LE>()?.into()];
+    reader.read_exact(&mut record)?;
+
+    if reader.read_u64::<LE>()? != xxhash_rust::xxh3::xxh3_64(&record) {
+        log::debug!("End of frame at {pos} is corrupt");
+        return Ok(None);
+    }
+
+    let mut entry = TocEntry {
+        name,
+        pos: data_pos,
+        len,
+        ..Default::default()
+    };
+
+    let mut record = &record[..];
+    entry.read_flags_from(&mut record)?;
+
+    let checksum = Checksum::from_raw(record.read_u128::<LE>()?);
+
+    if Checksum::from_raw(hasher.digest128()) != checksum {
+        log::debug!("Section data of frame at {pos} is corrupt");
+        return Ok(None);
+    }
+
+    Ok(Some((entry, checksum, reader.stream_position()?)))
+}
@@ -51,18 +51,76 @@
 #![allow(clippy::option_if_let_else)]
 #![warn(clippy::redundant_feature_names)]
 
+mod app_id;
+mod buffered_writer;
 mod checksum;
 mod checksum_writer;
+mod content_digest;
+mod detect;
+mod digest_writer;
+mod editor;
+
+#[cfg(feature = "encryption")]
+mod encryption;
+
 mod error;
+mod extract;
+mod frame;
+mod pack;
+mod progress;
+
+#[cfg(feature = "rayon")]
+mod par;
+
+mod parallel;
+
+#[cfg(feature = "parity")]
+mod parity;
+
 mod reader;
+mod recover;
+
+#[cfg(feature = "signing")]
+mod signing;
+
 mod toc;
 mod trailer;
+mod version;
+mod volume;
+mod write_summary;
 mod writer;
 
 pub(crate) type Result<T> = std::result::Result<T, Error>;
 
+pub use app_id::AppId;
 pub use checksum::Checksum;
-pub use error::Error;
-pub use reader::Reader;
-pub use toc::{entry::TocEntry, Toc};
+pub use content_digest::ContentDigest;
+pub use detect::{detect, ArchiveKind};
+pub use editor::ArchiveEditor;
+
+#[cfg(feature = "encryption")]
+pub use encryption::{DecryptingReader, EncryptionKey};
+
+pub use error::{Error, ErrorContext};
+pub use extract::{ExtractOptions, OnCollision};
+pub use pack::PackOptions;
+pub use progress::Progress;
+
+#[cfg(feature = "rayon")]
+pub use par::SectionReader;
+
+pub use parallel::{AssemblyOrder, ParallelWriter, SectionBuilder};
+pub use reader::{Reader, ReaderOptions, TocCopy};
+pub use recover::recover;
+
+#[cfg(feature = "signing")]
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+pub use toc::{
+    dir::DirEntry,
+    entry::{EncryptionInfo, SectionAttributes, TocEntry},
+    Toc,
+};
+pub use volume::{MultiVolumeReader, VolumeWriter};
+pub use write_summary::WriteSummary;
 pub use writer::Writer;
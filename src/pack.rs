@@ -0,0 +1,238 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    toc::{dir::SEPARATOR, entry::SectionName},
+    SectionAttributes, Writer,
+};
+use std::{
+    fs::{File, Metadata},
+    io::{Seek, Write},
+    path::Path,
+    sync::Arc,
+};
+
+type Filter = Arc<dyn Fn(&Path) -> bool + Send + Sync>;
+
+/// Options for packing a directory tree (see [`Writer::add_dir_recursive`])
+#[derive(Clone, Default)]
+pub struct PackOptions {
+    record_mode: bool,
+    record_mtime: bool,
+    include: Vec<Filter>,
+    exclude: Vec<Filter>,
+}
+
+impl std::fmt::Debug for PackOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PackOptions")
+            .field("record_mode", &self.record_mode)
+            .field("record_mtime", &self.record_mtime)
+            .field("include", &self.include.len())
+            .field("exclude", &self.exclude.len())
+            .finish()
+    }
+}
+
+impl PackOptions {
+    /// Records the file mode of every file as a section attribute.
+    ///
+    /// Has no effect on non-Unix platforms.
+    ///
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn record_mode(mut self, record: bool) -> Self {
+        self.record_mode = record;
+        self
+    }
+
+    /// Records the modification time of every file as a section attribute.
+    ///
+    /// Ignored in reproducible mode (see [`Writer::use_reproducible`]).
+    ///
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn record_mtime(mut self, record: bool) -> Self {
+        self.record_mtime = record;
+        self
+    }
+
+    /// Only adds files whose path (relative to the root) matches the given filter.
+    ///
+    /// If called multiple times, files must match all filters.
+    /// Directories are always descended into.
+    #[must_use]
+    pub fn include(mut self, filter: impl Fn(&Path) -> bool + Send + Sync + 'static) -> Self {
+        self.include.push(Arc::new(filter));
+        self
+    }
+
+    /// Skips files and directories whose path (relative to the root) matches the given filter.
+    ///
+    /// Excluded directories are not descended into.
+    #[must_use]
+    pub fn exclude(mut self, filter: impl Fn(&Path) -> bool + Send + Sync + 'static) -> Self {
+        self.exclude.push(Arc::new(filter));
+        self
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        self.exclude.iter().any(|filter| filter(path))
+    }
+
+    fn is_included(&self, path: &Path) -> bool {
+        self.include.iter().all(|filter| filter(path))
+    }
+
+    fn attributes(&self, metadata: &Metadata) -> std::io::Result<Option<SectionAttributes>> {
+        let mut attributes = SectionAttributes::default();
+
+        #[cfg(unix)]
+        if self.record_mode {
+            use std::os::unix::fs::PermissionsExt;
+            attributes = attributes.with_mode(metadata.permissions().mode() & 0o7777);
+        }
+
+        if self.record_mtime {
+            attributes = attributes.with_mtime(metadata.modified()?);
+        }
+
+        Ok((attributes != SectionAttributes::default()).then_some(attributes))
+    }
+}
+
+/// Maps a relative path to a `/`-separated section name.
+///
+/// Fails on non-Unix platforms, if the path is not valid UTF-8.
+#[cfg_attr(unix, allow(clippy::unnecessary_wraps))]
+fn section_name(relative: &Path) -> std::io::Result<SectionName> {
+    let mut name = SectionName::new();
+
+    for component in relative.components() {
+        #[cfg(unix)]
+        let component =
+            <std::ffi::OsStr as std::os::unix::ffi::OsStrExt>::as_bytes(component.as_os_str());
+
+        #[cfg(not(unix))]
+        let component = component
+            .as_os_str()
+            .to_str()
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{} is not valid UTF-8", relative.display()),
+                )
+            })?
+            .as_bytes();
+
+        if !name.is_empty() {
+            name.push(SEPARATOR);
+        }
+        name.extend_from_slice(component);
+    }
+
+    Ok(name)
+}
+
+impl<W: Write + Seek> Writer<W> {
+    /// Adds a file as a new section.
+    ///
+    /// The file contents are streamed into the archive. To record file attributes,
+    /// call [`Writer::set_attributes`] afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the name is invalid
+    /// (see [`Writer::use_path_validation`]).
+    pub fn add_file(
+        &mut self,
+        name: impl Into<SectionName>,
+        path: impl AsRef<Path>,
+    ) -> std::io::Result<()> {
+        let file = File::open(path)?;
+        self.write_section_from_file(name, &file)?;
+        Ok(())
+    }
+
+    /// Adds all files in a directory tree, one section per file.
+    ///
+    /// Sections are named after the file paths relative to the root, using `/` as separator,
+    /// and are added depth-first, sorted by name, so the same tree always results
+    /// in the same archive.
+    ///
+    /// Symbolic links and empty directories are skipped.
+    ///
+    /// Returns the number of added files.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the root is not a directory.
+    pub fn add_dir_recursive(
+        &mut self,
+        root: impl AsRef<Path>,
+        options: &PackOptions,
+    ) -> std::io::Result<usize> {
+        let root = root.as_ref();
+
+        if !std::fs::metadata(root)?.is_dir() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not a directory", root.display()),
+            ));
+        }
+
+        let mut count = 0;
+        self.add_dir_entries(root, Path::new(""), options, &mut count)?;
+        Ok(count)
+    }
+
+    fn add_dir_entries(
+        &mut self,
+        root: &Path,
+        relative: &Path,
+        options: &PackOptions,
+        count: &mut usize,
+    ) -> std::io::Result<()> {
+        let mut children = std::fs::read_dir(root.join(relative))?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        // NOTE: Directory iteration order is platform-specific
+        children.sort();
+
+        for child in children {
+            let relative = relative.join(child);
+
+            if options.is_excluded(&relative) {
+                log::trace!("Excluding {}", relative.display());
+                continue;
+            }
+
+            let path = root.join(&relative);
+            let metadata = std::fs::symlink_metadata(&path)?;
+
+            if metadata.is_dir() {
+                self.add_dir_entries(root, &relative, options, count)?;
+            } else if metadata.is_file() {
+                if !options.is_included(&relative) {
+                    continue;
+                }
+
+                log::trace!("Adding {}", relative.display());
+
+                self.add_file(section_name(&relative)?, &path)?;
+
+                if let Some(attributes) = options.attributes(&metadata)? {
+                    self.set_attributes(attributes)?;
+                }
+
+                *count += 1;
+            } else {
+                log::debug!("Skipping {}, which is not a regular file", path.display());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{toc::Toc, Checksum, ErrorContext, Progress, TocEntry};
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+};
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> std::io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> std::io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn read_at(_: &File, _: &mut [u8], _: u64) -> std::io::Result<usize> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "positional reads are not supported on this platform",
+    ))
+}
+
+/// Reader of a single section, using positional reads on a shared file handle
+///
+/// Positions are relative to the start of the section.
+pub struct SectionReader<'a> {
+    file: &'a File,
+    start: u64,
+    len: u64,
+    pos: u64,
+}
+
+impl<'a> SectionReader<'a> {
+    pub(crate) fn new(file: &'a File, entry: &TocEntry) -> Self {
+        Self {
+            file,
+            start: entry.pos(),
+            len: entry.len(),
+            pos: 0,
+        }
+    }
+}
+
+impl Read for SectionReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.len.saturating_sub(self.pos);
+
+        #[allow(clippy::cast_possible_truncation)]
+        let max = (buf.len() as u64).min(remaining) as usize;
+
+        #[allow(clippy::indexing_slicing)]
+        let n = read_at(self.file, &mut buf[..max], self.start + self.pos)?;
+
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for SectionReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+
+        let Some(new_pos) = new_pos else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            ));
+        };
+
+        self.pos = new_pos;
+        Ok(new_pos)
+    }
+}
+
+/// Reads a section, and checks its checksum (if it has one).
+fn verify_section(
+    file: &File,
+    entry: &TocEntry,
+    progress: Option<&dyn Progress>,
+) -> crate::Result<()> {
+    if let Some(progress) = progress {
+        progress.on_section_start(entry.name());
+    }
+
+    let mut reader = SectionReader::new(file, entry);
+    let mut hasher = xxhash_rust::xxh3::Xxh3Default::new();
+    let mut buf = vec![0; 64 * 1_024];
+    let mut read = 0;
+
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+
+        #[allow(clippy::indexing_slicing)]
+        hasher.update(&buf[..n]);
+
+        if let Some(progress) = progress {
+            progress.on_bytes(n as u64);
+        }
+
+        read += n as u64;
+    }
+
+    if read != entry.len() {
+        return Err(crate::Error::Io(std::io::Error::from(
+            std::io::ErrorKind::UnexpectedEof,
+        )));
+    }
+
+    if let Some(expected) = entry.checksum() {
+        Checksum::from_raw(hasher.digest128())
+            .check(expected, ErrorContext::section(entry.name(), entry.pos()))?;
+    }
+
+    if let Some(progress) = progress {
+        progress.on_section_end(entry);
+    }
+
+    Ok(())
+}
+
+pub fn verify(file: &File, toc: &Toc, progress: Option<&dyn Progress>) -> Vec<crate::Error> {
+    // NOTE: Sections that share their data (see `Writer::use_deduplication`)
+    // have the same checksum, so their data only needs to be verified once
+    let mut ranges = HashSet::new();
+
+    let entries = toc
+        .header()
+        .into_iter()
+        .chain(toc.iter())
+        .filter(|entry| {
+            ranges.insert((
+                entry.pos(),
+                entry.len(),
+                entry.checksum().map(Checksum::into_u128),
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    entries
+        .into_par_iter()
+        .filter_map(|entry| verify_section(file, entry, progress).err())
+        .collect()
+}
+
+pub fn for_each_section<F>(file: &File, toc: &Toc, f: F) -> Vec<crate::Error>
+where
+    F: Fn(&TocEntry, &mut SectionReader<'_>) -> crate::Result<()> + Sync,
+{
+    toc.par_iter()
+        .filter_map(|entry| f(entry, &mut SectionReader::new(file, entry)).err())
+        .collect()
+}
@@ -0,0 +1,372 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{toc::entry::SectionName, SectionAttributes, WriteSummary, Writer};
+use std::{
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Default amount of bytes a section builder keeps in memory before spilling to a file
+pub const DEFAULT_SPILL_THRESHOLD: usize = 4 * 1_024 * 1_024;
+
+/// Order in which a [`ParallelWriter`] assembles the sections
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum AssemblyOrder {
+    /// Order in which the section builders were created
+    #[default]
+    Created,
+
+    /// Order in which the section builders were finished
+    ///
+    /// Note that this is not deterministic, if sections are finished on multiple threads,
+    /// so it cannot be used with a reproducible writer (see [`Writer::use_reproducible`]).
+    Finished,
+
+    /// Sorted by section name (keeping the creation order of duplicate names)
+    Name,
+}
+
+/// Temporary file that is removed when dropped
+struct SpillFile {
+    path: PathBuf,
+    file: BufWriter<File>,
+}
+
+impl SpillFile {
+    fn create(dir: &Path) -> std::io::Result<Self> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        loop {
+            let path = dir.join(format!(
+                ".sfa-spill.{}.{}.tmp",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed),
+            ));
+
+            match File::options()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(file) => {
+                    return Ok(Self {
+                        path,
+                        file: BufWriter::new(file),
+                    })
+                }
+                // NOTE: Left over by an earlier process with the same ID
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            log::warn!("Failed to remove spill file {}: {e:?}", self.path.display());
+        }
+    }
+}
+
+enum SectionData {
+    Memory(Vec<u8>),
+    File(SpillFile),
+}
+
+struct FinishedSection {
+    index: u64,
+    name: SectionName,
+    data: SectionData,
+    attributes: Option<SectionAttributes>,
+}
+
+struct Shared {
+    sections: Mutex<Vec<FinishedSection>>,
+    next_index: AtomicU64,
+}
+
+/// Builder of a single section of a [`ParallelWriter`]
+///
+/// Section builders are independent of each other, so they can be
+/// written on different threads. The data is kept in memory, and spilled to
+/// a temporary file once it exceeds the spill threshold
+/// (see [`ParallelWriter::use_spill_threshold`]).
+///
+/// The section is only added to the archive once [`SectionBuilder::finish`] is called;
+/// dropping the builder discards it.
+pub struct SectionBuilder {
+    shared: Arc<Shared>,
+    index: u64,
+    name: SectionName,
+    buffer: Vec<u8>,
+    spill_threshold: usize,
+    spill_dir: PathBuf,
+    spill_file: Option<SpillFile>,
+    attributes: Option<SectionAttributes>,
+}
+
+impl SectionBuilder {
+    /// Returns the name of the section.
+    #[must_use]
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    /// Sets the file attributes of the section (see [`Writer::set_attributes`]).
+    pub fn set_attributes(&mut self, attributes: SectionAttributes) {
+        self.attributes = Some(attributes);
+    }
+
+    /// Finishes the section, so it is added to the archive.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    ///
+    /// # Panics
+    ///
+    /// Panics if another thread panicked while finishing a section.
+    pub fn finish(mut self) -> std::io::Result<()> {
+        let data = match self.spill_file.take() {
+            Some(mut spill_file) => {
+                spill_file.file.flush()?;
+                SectionData::File(spill_file)
+            }
+            None => SectionData::Memory(std::mem::take(&mut self.buffer)),
+        };
+
+        #[allow(clippy::expect_used)]
+        self.shared
+            .sections
+            .lock()
+            .expect("lock is poisoned")
+            .push(FinishedSection {
+                index: self.index,
+                name: std::mem::take(&mut self.name),
+                data,
+                attributes: self.attributes,
+            });
+
+        Ok(())
+    }
+}
+
+impl Write for SectionBuilder {
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Some(spill_file) = &mut self.spill_file {
+            return spill_file.file.write(buf);
+        }
+
+        if self.buffer.len() + buf.len() > self.spill_threshold {
+            log::trace!(
+                "Spilling section {:?} to disk",
+                String::from_utf8_lossy(&self.name)
+            );
+
+            let mut spill_file = SpillFile::create(&self.spill_dir)?;
+            spill_file.file.write_all(&self.buffer)?;
+            self.buffer = Vec::new();
+
+            let n = spill_file.file.write(buf)?;
+            self.spill_file = Some(spill_file);
+            return Ok(n);
+        }
+
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+}
+
+/// Archive writer that builds sections independently (e.g. on multiple threads),
+/// and assembles them into a single archive when finishing
+///
+/// The assembled archive is a regular archive, written by the given [`Writer`],
+/// so all of its options apply.
+///
+/// ```
+/// # use sfa::{ParallelWriter, Reader, Writer};
+/// # use std::io::Write;
+/// # let dir = tempfile::tempdir()?;
+/// # let path = dir.path().join("hello.sfa");
+/// let file = std::fs::File::create(&path)?;
+/// let writer = ParallelWriter::new(Writer::from_writer(file));
+///
+/// std::thread::scope(|scope| {
+///     for idx in 0..4 {
+///         let mut section = writer.section(format!("section{idx}"));
+///
+///         scope.spawn(move || {
+///             section.write_all(b"hello")?;
+///             section.finish()
+///         });
+///     }
+/// });
+///
+/// writer.finish()?;
+///
+/// let reader = Reader::new(&path)?;
+/// assert_eq!(4, reader.toc().len());
+/// assert_eq!(b"section0", reader.toc()[0].name());
+/// #
+/// # Ok::<(), sfa::Error>(())
+/// ```
+pub struct ParallelWriter<W: Write + Seek> {
+    writer: Writer<W>,
+    shared: Arc<Shared>,
+    order: AssemblyOrder,
+    spill_threshold: usize,
+    spill_dir: PathBuf,
+}
+
+impl<W: Write + Seek> ParallelWriter<W> {
+    /// Creates a new parallel writer, which assembles the sections using the given writer.
+    #[must_use]
+    pub fn new(writer: Writer<W>) -> Self {
+        Self {
+            writer,
+            shared: Arc::new(Shared {
+                sections: Mutex::default(),
+                next_index: AtomicU64::new(0),
+            }),
+            order: AssemblyOrder::default(),
+            spill_threshold: DEFAULT_SPILL_THRESHOLD,
+            spill_dir: std::env::temp_dir(),
+        }
+    }
+
+    /// Sets the amount of bytes a section builder keeps in memory,
+    /// before spilling the section to a temporary file.
+    ///
+    /// Defaults to 4 MiB.
+    ///
+    /// Only applies to section builders created after this call.
+    #[must_use]
+    pub fn use_spill_threshold(mut self, bytes: usize) -> Self {
+        self.spill_threshold = bytes;
+        self
+    }
+
+    /// Sets the folder for temporary spill files.
+    ///
+    /// Defaults to [`std::env::temp_dir`].
+    ///
+    /// Only applies to section builders created after this call.
+    #[must_use]
+    pub fn use_spill_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.spill_dir = dir.into();
+        self
+    }
+
+    /// Sets the order in which the sections are assembled.
+    ///
+    /// Defaults to [`AssemblyOrder::Created`].
+    ///
+    /// [`AssemblyOrder::Finished`] is refused when assembling, if the writer is reproducible.
+    #[must_use]
+    pub fn use_order(mut self, order: AssemblyOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Creates a builder for a new section.
+    pub fn section(&self, name: impl Into<SectionName>) -> SectionBuilder {
+        SectionBuilder {
+            shared: self.shared.clone(),
+            index: self.shared.next_index.fetch_add(1, Ordering::Relaxed),
+            name: name.into(),
+            buffer: Vec::new(),
+            spill_threshold: self.spill_threshold,
+            spill_dir: self.spill_dir.clone(),
+            spill_file: None,
+            attributes: None,
+        }
+    }
+
+    fn assemble(&mut self) -> crate::Result<()> {
+        if self.order == AssemblyOrder::Finished && self.writer.is_reproducible() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "finished order cannot be used in reproducible mode",
+            )
+            .into());
+        }
+
+        let mut sections = {
+            #[allow(clippy::expect_used)]
+            let mut sections = self.shared.sections.lock().expect("lock is poisoned");
+            std::mem::take(&mut *sections)
+        };
+
+        match self.order {
+            AssemblyOrder::Created => sections.sort_by_key(|section| section.index),
+            AssemblyOrder::Finished => {}
+            AssemblyOrder::Name => {
+                sections.sort_by(|a, b| a.name.cmp(&b.name).then(a.index.cmp(&b.index)));
+            }
+        }
+
+        log::trace!("Assembling {} sections", sections.len());
+
+        for section in sections {
+            match section.data {
+                SectionData::Memory(buffer) => {
+                    self.writer.start(section.name)?;
+                    self.writer.write_all(&buffer)?;
+                }
+                SectionData::File(mut spill_file) => {
+                    let file = spill_file.file.get_mut();
+                    file.seek(SeekFrom::Start(0))?;
+                    self.writer.write_section_from_file(section.name, file)?;
+                }
+            }
+
+            if let Some(attributes) = section.attributes {
+                self.writer.set_attributes(attributes)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Assembles all finished sections, and finishes the archive.
+    ///
+    /// Sections whose builders were not finished (yet) are not included.
+    ///
+    /// Returns a summary of the written archive.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or [`AssemblyOrder::Finished`]
+    /// is used with a reproducible writer.
+    pub fn finish(mut self) -> crate::Result<WriteSummary> {
+        self.assemble()?;
+        self.writer.finish()
+    }
+
+    /// Assembles all finished sections, and finishes the archive.
+    ///
+    /// Returns the inner writer, and a summary of the written archive.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or [`AssemblyOrder::Finished`]
+    /// is used with a reproducible writer.
+    pub fn into_inner_with_summary(mut self) -> crate::Result<(W, WriteSummary)> {
+        self.assemble()?;
+        self.writer.into_inner_with_summary()
+    }
+}
@@ -0,0 +1,451 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{toc::Toc, Checksum, ErrorContext, TocEntry};
+use byteorder::{ReadBytesExt, LE};
+use reed_solomon_erasure::galois_8::ReedSolomon;
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+/// Magic bytes at the start of the parity section
+const PARITY_MAGIC: &[u8] = b"PAR!";
+
+/// Size of the parity metadata before the shard checksums
+/// (magic, block size, data shard count, parity shard count, region length)
+const PARITY_HEADER_SIZE: u64 = PARITY_MAGIC.len() as u64 + 4 + 1 + 1 + 8;
+
+/// Default size of a parity block
+pub const DEFAULT_PARITY_BLOCK_SIZE: u32 = 4_096;
+
+/// Maximum size of a parity block, which bounds the memory needed for a stripe
+pub const MAX_PARITY_BLOCK_SIZE: u32 = 1_024 * 1_024;
+
+/// Layout of the parity blocks
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ParityConfig {
+    pub block_size: u32,
+    pub data_shards: u8,
+    pub parity_shards: u8,
+}
+
+impl ParityConfig {
+    fn codec(self) -> Result<ReedSolomon, reed_solomon_erasure::Error> {
+        ReedSolomon::new(self.data_shards.into(), self.parity_shards.into())
+    }
+
+    fn shards_per_stripe(self) -> usize {
+        usize::from(self.data_shards) + usize::from(self.parity_shards)
+    }
+
+    fn stripe_len(self) -> u64 {
+        u64::from(self.block_size) * u64::from(self.data_shards)
+    }
+
+    fn stripe_count(self, region_len: u64) -> u64 {
+        region_len.div_ceil(self.stripe_len())
+    }
+
+    /// Returns the size of the parity section for a region of the given length.
+    fn section_len(self, region_len: u64) -> u64 {
+        let stripes = self.stripe_count(region_len);
+
+        PARITY_HEADER_SIZE
+            + stripes * self.shards_per_stripe() as u64 * 8
+            + 8
+            + stripes * u64::from(self.parity_shards) * u64::from(self.block_size)
+    }
+}
+
+/// Computes the parity of everything written through it, stripe by stripe
+pub struct ParityEncoder {
+    config: ParityConfig,
+    codec: ReedSolomon,
+
+    /// Data of the current (incomplete) stripe
+    stripe: Vec<u8>,
+
+    region_len: u64,
+
+    /// Checksums of all shards (data and parity), in stripe order
+    shard_checksums: Vec<u64>,
+
+    parity: Vec<u8>,
+}
+
+impl ParityEncoder {
+    pub fn new(config: ParityConfig) -> std::io::Result<Self> {
+        if config.block_size == 0 || config.block_size > MAX_PARITY_BLOCK_SIZE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "parity block size must be between 1 byte and 1 MiB",
+            ));
+        }
+
+        let codec = config.codec().map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid parity shard counts: {e:?}"),
+            )
+        })?;
+
+        Ok(Self {
+            config,
+            codec,
+            #[allow(clippy::cast_possible_truncation)]
+            stripe: Vec::with_capacity(config.stripe_len() as usize),
+            region_len: 0,
+            shard_checksums: Vec::new(),
+            parity: Vec::new(),
+        })
+    }
+
+    pub fn update(&mut self, mut buf: &[u8]) {
+        self.region_len += buf.len() as u64;
+
+        #[allow(clippy::cast_possible_truncation)]
+        let stripe_len = self.config.stripe_len() as usize;
+
+        while !buf.is_empty() {
+            let n = (stripe_len - self.stripe.len()).min(buf.len());
+            let (head, tail) = buf.split_at(n);
+
+            self.stripe.extend_from_slice(head);
+            buf = tail;
+
+            if self.stripe.len() == stripe_len {
+                self.encode_stripe();
+            }
+        }
+    }
+
+    fn encode_stripe(&mut self) {
+        let block_size = self.config.block_size as usize;
+
+        // NOTE: The last stripe is padded with zeros
+        #[allow(clippy::cast_possible_truncation)]
+        self.stripe.resize(self.config.stripe_len() as usize, 0);
+
+        let mut shards = self
+            .stripe
+            .chunks(block_size)
+            .map(<[u8]>::to_vec)
+            .chain(std::iter::repeat_n(
+                vec![0; block_size],
+                self.config.parity_shards.into(),
+            ))
+            .collect::<Vec<_>>();
+
+        #[allow(clippy::expect_used)]
+        self.codec
+            .encode(&mut shards)
+            .expect("shards should match the codec");
+
+        self.shard_checksums
+            .extend(shards.iter().map(|shard| xxhash_rust::xxh3::xxh3_64(shard)));
+
+        for shard in shards.iter().skip(self.config.data_shards.into()) {
+            self.parity.extend_from_slice(shard);
+        }
+
+        self.stripe.clear();
+    }
+
+    /// Encodes the last stripe, and returns the contents of the parity section.
+    pub fn finish(mut self) -> Vec<u8> {
+        if !self.stripe.is_empty() {
+            self.encode_stripe();
+        }
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(PARITY_MAGIC);
+        bytes.extend_from_slice(&self.config.block_size.to_le_bytes());
+        bytes.push(self.config.data_shards);
+        bytes.push(self.config.parity_shards);
+        bytes.extend_from_slice(&self.region_len.to_le_bytes());
+
+        for checksum in &self.shard_checksums {
+            bytes.extend_from_slice(&checksum.to_le_bytes());
+        }
+
+        let metadata_checksum = xxhash_rust::xxh3::xxh3_64(&bytes);
+        bytes.extend_from_slice(&metadata_checksum.to_le_bytes());
+
+        bytes.extend_from_slice(&self.parity);
+
+        bytes
+    }
+}
+
+/// Parity metadata, as read from the parity section
+struct ParityMetadata {
+    config: ParityConfig,
+    region_len: u64,
+    shard_checksums: Vec<u64>,
+
+    /// Position of the first parity block
+    parity_pos: u64,
+}
+
+fn unrepairable(reason: &'static str, offset: u64) -> crate::Error {
+    crate::Error::Unrepairable {
+        reason,
+        context: ErrorContext::at(offset),
+    }
+}
+
+impl ParityMetadata {
+    fn read_from(file: &mut File, entry: &TocEntry) -> crate::Result<Self> {
+        let corrupted = || unrepairable("parity metadata is corrupted", entry.pos());
+
+        if entry.len() < PARITY_HEADER_SIZE {
+            return Err(corrupted());
+        }
+
+        file.seek(SeekFrom::Start(entry.pos()))?;
+
+        #[allow(clippy::cast_possible_truncation)]
+        let mut header = vec![0; PARITY_HEADER_SIZE as usize];
+        file.read_exact(&mut header)?;
+
+        let (magic, mut rest) = header.split_at(PARITY_MAGIC.len());
+
+        if magic != PARITY_MAGIC {
+            return Err(corrupted());
+        }
+
+        let config = ParityConfig {
+            block_size: rest.read_u32::<LE>()?,
+            data_shards: rest.read_u8()?,
+            parity_shards: rest.read_u8()?,
+        };
+        let region_len = rest.read_u64::<LE>()?;
+
+        // NOTE: Don't trust the metadata for allocations, before its checksum is verified
+        if config.block_size == 0
+            || config.block_size > MAX_PARITY_BLOCK_SIZE
+            || config.codec().is_err()
+            || region_len != entry.pos()
+            || config.section_len(region_len) != entry.len()
+        {
+            return Err(corrupted());
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let checksum_count = config.stripe_count(region_len) as usize * config.shards_per_stripe();
+
+        let mut checksums = vec![0; checksum_count * 8];
+        file.read_exact(&mut checksums)?;
+
+        let metadata_checksum = file.read_u64::<LE>()?;
+
+        let mut hasher = xxhash_rust::xxh3::Xxh3Default::new();
+        hasher.update(&header);
+        hasher.update(&checksums);
+
+        if hasher.digest() != metadata_checksum {
+            return Err(corrupted());
+        }
+
+        let shard_checksums = checksums
+            .chunks_exact(8)
+            .map(|mut chunk| chunk.read_u64::<LE>())
+            .collect::<std::io::Result<_>>()?;
+
+        Ok(Self {
+            config,
+            region_len,
+            shard_checksums,
+            parity_pos: file.stream_position()?,
+        })
+    }
+
+    /// Returns the position of the given shard of the given stripe.
+    fn shard_pos(&self, stripe: u64, shard: usize) -> u64 {
+        let block_size = u64::from(self.config.block_size);
+        let data_shards = usize::from(self.config.data_shards);
+
+        if shard < data_shards {
+            (stripe * u64::from(self.config.data_shards) + shard as u64) * block_size
+        } else {
+            self.parity_pos
+                + (stripe * u64::from(self.config.parity_shards) + (shard - data_shards) as u64)
+                    * block_size
+        }
+    }
+
+    /// Returns the number of bytes of the given shard that are stored in the file
+    /// (the rest of the last data shards is padding).
+    fn stored_len(&self, stripe: u64, shard: usize) -> usize {
+        let block_size = u64::from(self.config.block_size);
+
+        let len = if shard < self.config.data_shards.into() {
+            self.region_len
+                .saturating_sub(self.shard_pos(stripe, shard))
+                .min(block_size)
+        } else {
+            block_size
+        };
+
+        #[allow(clippy::cast_possible_truncation)]
+        let len = len as usize;
+        len
+    }
+
+    /// Repairs the damaged shards of a stripe.
+    ///
+    /// Returns the number of repaired shards.
+    fn repair_stripe(&self, file: &mut File, stripe: u64) -> crate::Result<usize> {
+        let shard_count = self.config.shards_per_stripe();
+
+        #[allow(clippy::cast_possible_truncation)]
+        let first_checksum = stripe as usize * shard_count;
+
+        let mut shards = Vec::with_capacity(shard_count);
+        let mut damaged = vec![];
+
+        for shard in 0..shard_count {
+            let mut buf = vec![0; self.config.block_size as usize];
+
+            #[allow(clippy::indexing_slicing)]
+            let stored = &mut buf[..self.stored_len(stripe, shard)];
+
+            file.seek(SeekFrom::Start(self.shard_pos(stripe, shard)))?;
+            file.read_exact(stored)?;
+
+            let expected = self.shard_checksums.get(first_checksum + shard).copied();
+
+            if expected == Some(xxhash_rust::xxh3::xxh3_64(&buf)) {
+                shards.push(Some(buf));
+            } else {
+                shards.push(None);
+                damaged.push(shard);
+            }
+        }
+
+        if damaged.is_empty() {
+            return Ok(0);
+        }
+
+        log::debug!("Repairing shards {damaged:?} of stripe {stripe}");
+
+        if damaged.len() > self.config.parity_shards.into() {
+            return Err(unrepairable(
+                "too many damaged blocks",
+                self.shard_pos(stripe, 0),
+            ));
+        }
+
+        #[allow(clippy::expect_used)]
+        self.config
+            .codec()
+            .expect("codec was already checked")
+            .reconstruct(&mut shards)
+            .map_err(|_| unrepairable("too many damaged blocks", self.shard_pos(stripe, 0)))?;
+
+        for &shard in &damaged {
+            let Some(Some(buf)) = shards.get(shard) else {
+                continue;
+            };
+
+            #[allow(clippy::indexing_slicing)]
+            let stored = &buf[..self.stored_len(stripe, shard)];
+
+            file.seek(SeekFrom::Start(self.shard_pos(stripe, shard)))?;
+            file.write_all(stored)?;
+        }
+
+        Ok(damaged.len())
+    }
+}
+
+fn section_checksum(file: &mut File, entry: &TocEntry) -> crate::Result<Checksum> {
+    file.seek(SeekFrom::Start(entry.pos()))?;
+
+    let mut hasher = xxhash_rust::xxh3::Xxh3Default::new();
+    let mut reader = std::io::Read::take(&mut *file, entry.len());
+    let mut buf = vec![0; 64 * 1_024];
+
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+
+        #[allow(clippy::indexing_slicing)]
+        hasher.update(&buf[..n]);
+    }
+
+    Ok(Checksum::from_raw(hasher.digest128()))
+}
+
+/// Returns the sections (including the header) whose checksums do not match,
+/// or `None` if not all sections have checksums.
+fn damaged_sections<'a>(file: &mut File, toc: &'a Toc) -> crate::Result<Option<Vec<&'a TocEntry>>> {
+    let mut damaged = vec![];
+
+    for entry in toc.header().into_iter().chain(toc.iter()) {
+        let Some(expected) = entry.checksum() else {
+            return Ok(None);
+        };
+
+        if section_checksum(file, entry)? != expected {
+            damaged.push(entry);
+        }
+    }
+
+    Ok(Some(damaged))
+}
+
+/// Reads the layout of the parity blocks of an archive.
+pub fn read_config(file: &mut File, toc: &Toc) -> crate::Result<Option<ParityConfig>> {
+    toc.parity()
+        .map(|entry| ParityMetadata::read_from(file, entry).map(|metadata| metadata.config))
+        .transpose()
+}
+
+pub fn repair(file: &mut File, toc: &Toc) -> crate::Result<usize> {
+    let Some(entry) = toc.parity() else {
+        return Err(unrepairable("archive has no parity section", 0));
+    };
+
+    let metadata = ParityMetadata::read_from(file, entry)?;
+    let stripe_len = metadata.config.stripe_len();
+
+    // NOTE: Section checksums tell us where to look for damage,
+    // otherwise every stripe is checked
+    let damaged = damaged_sections(file, toc)?;
+
+    let stripes = match &damaged {
+        Some(damaged) => damaged
+            .iter()
+            .filter(|entry| entry.len() > 0)
+            .flat_map(|entry| {
+                (entry.pos() / stripe_len)..=((entry.pos() + entry.len() - 1) / stripe_len)
+            })
+            .collect::<BTreeSet<_>>(),
+        None => (0..metadata.config.stripe_count(metadata.region_len)).collect(),
+    };
+
+    let mut repaired = 0;
+
+    for stripe in stripes {
+        repaired += metadata.repair_stripe(file, stripe)?;
+    }
+
+    for entry in damaged.into_iter().flatten() {
+        if Some(section_checksum(file, entry)?) != entry.checksum() {
+            return Err(crate::Error::Unrepairable {
+                reason: "section could not be repaired",
+                context: ErrorContext::section(entry.name(), entry.pos()),
+            });
+        }
+    }
+
+    file.sync_all()?;
+
+    Ok(repaired)
+}
@@ -0,0 +1,35 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::TocEntry;
+
+/// Observer of the progress of writing or reading an archive,
+/// e.g. to drive progress bars or collect metrics
+///
+/// See [`crate::Writer::use_progress`] and [`crate::Reader::use_progress`].
+///
+/// All methods do nothing by default, so only the relevant ones need to be implemented.
+///
+/// Sections may be processed concurrently (see `Reader::par_verify`),
+/// in which case the calls of different sections are interleaved.
+pub trait Progress: Send + Sync {
+    /// Called when a section is started, before any of its data is processed.
+    ///
+    /// The header, as well as data written before the first named section, has an empty name.
+    fn on_section_start(&self, name: &[u8]) {
+        let _ = name;
+    }
+
+    /// Called after `n` bytes of section data were processed.
+    ///
+    /// When writing, these are the bytes passed to the writer (before encrypting them).
+    fn on_bytes(&self, n: u64) {
+        let _ = n;
+    }
+
+    /// Called when a section is finished.
+    fn on_section_end(&self, entry: &TocEntry) {
+        let _ = entry;
+    }
+}
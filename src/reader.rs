@@ -3,14 +3,80 @@
 // (found in the LICENSE-* files in the repository)
 
 use crate::{
-    toc::{reader::TocReader, Toc},
-    trailer::reader::TrailerReader,
+    toc::{reader::TocReader, Toc, TocEncoding},
+    trailer::reader::{ParsedTrailer, TrailerReader},
+    AppId, ErrorContext, Progress, TocEntry,
 };
-use std::io::{BufReader, Read, Seek};
+use std::{
+    io::{BufReader, Read, Seek},
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// Options for opening an archive
+#[derive(Clone, Debug, Default)]
+pub struct ReaderOptions {
+    expected_app_id: Option<AppId>,
+}
+
+impl ReaderOptions {
+    /// Requires the archive to have the given application ID (see [`crate::Writer::use_app_id`]).
+    ///
+    /// Opening an archive with a different (or without any) application ID
+    /// fails with [`crate::Error::AppIdMismatch`], before the table of contents is read.
+    #[must_use]
+    pub fn expect_app_id(mut self, app_id: impl Into<AppId>) -> Self {
+        self.expected_app_id = Some(app_id.into());
+        self
+    }
+
+    fn check(&self, trailer: &ParsedTrailer) -> crate::Result<()> {
+        if let Some(expected) = self.expected_app_id {
+            let got = trailer.extension.app_id.map(|(app_id, _)| app_id);
+
+            if got != Some(expected) {
+                log::error!("Application ID mismatch, expected {expected:?}, got {got:?}");
+                return Err(crate::Error::AppIdMismatch {
+                    expected,
+                    got,
+                    context: ErrorContext::at(trailer.trailer_pos),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Copy of the table of contents that an archive was read from
+/// (see [`crate::Writer::use_redundant_toc`])
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum TocCopy {
+    /// The primary table of contents and trailer at the end of the file
+    #[default]
+    Primary,
+
+    /// The backup copy, because the primary table of contents or trailer could not be read
+    Backup,
+}
 
 /// Archive reader
 pub struct Reader {
     toc: Toc,
+    app_id: Option<(AppId, u32)>,
+
+    /// Path of the archive, if it was opened using a path
+    path: Option<PathBuf>,
+
+    toc_copy: TocCopy,
+
+    pub(crate) toc_encoding: TocEncoding,
+
+    /// Whether the archive has a backup copy of the table of contents
+    pub(crate) redundant_toc: bool,
+
+    /// Observer that is notified about read sections and bytes
+    progress: Option<Arc<dyn Progress>>,
 }
 
 impl Reader {
@@ -20,11 +86,28 @@ impl Reader {
     ///
     /// Returns error, if an IO error occurred.
     pub fn new(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        Self::with_options(path, &ReaderOptions::default())
+    }
+
+    /// Creates a new [`Reader`] from a file path, using the given options.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the archive does not match the options.
+    pub fn with_options(
+        path: impl AsRef<std::path::Path>,
+        options: &ReaderOptions,
+    ) -> crate::Result<Self> {
+        let path = path.as_ref();
+
         let file = std::fs::File::open(path)?;
         let mut file = BufReader::with_capacity(4_096, file);
-        let trailer = TrailerReader::from_reader(&mut file)?;
-        let toc = TocReader::from_reader(&mut file, trailer.toc_pos, trailer.toc_checksum)?;
-        Ok(Self { toc })
+
+        let mut reader =
+            Self::from_reader_with_options(&mut file, options).map_err(|e| e.with_path(path))?;
+        reader.path = Some(path.to_path_buf());
+
+        Ok(reader)
     }
 
     /// Creates a new [`Reader`] from a reader.
@@ -32,10 +115,224 @@ impl Reader {
     /// # Errors
     ///
     /// Returns error, if an IO error occurred.
-    pub fn from_reader<R: Read + Seek>(mut reader: &mut R) -> crate::Result<Self> {
-        let trailer = TrailerReader::from_reader(&mut reader)?;
-        let toc = TocReader::from_reader(&mut reader, trailer.toc_pos, trailer.toc_checksum)?;
-        Ok(Self { toc })
+    pub fn from_reader<R: Read + Seek>(reader: &mut R) -> crate::Result<Self> {
+        Self::from_reader_with_options(reader, &ReaderOptions::default())
+    }
+
+    /// Creates a new [`Reader`] from a reader, using the given options.
+    ///
+    /// Archives that are split into volumes are refused,
+    /// because their sections are stored in other files (see [`crate::MultiVolumeReader`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the archive does not match the options.
+    pub fn from_reader_with_options<R: Read + Seek>(
+        reader: &mut R,
+        options: &ReaderOptions,
+    ) -> crate::Result<Self> {
+        let archive = Self::read_archive(reader, options)?;
+
+        if let Some(entry) = archive
+            .toc
+            .header()
+            .into_iter()
+            .chain(archive.toc.iter())
+            .find(|entry| entry.volume().is_some())
+        {
+            log::error!("Archive is split into volumes");
+            return Err(crate::Error::MultiVolume {
+                context: ErrorContext::section(entry.name(), entry.pos()),
+            });
+        }
+
+        Ok(archive)
+    }
+
+    /// Reads the table of contents, including entries of sections in other volumes.
+    pub(crate) fn read_archive<R: Read + Seek>(
+        mut reader: &mut R,
+        options: &ReaderOptions,
+    ) -> crate::Result<Self> {
+        let (trailer, toc, toc_copy) = match Self::read_toc(&mut reader, options, None) {
+            Ok((trailer, toc)) => (trailer, toc, TocCopy::Primary),
+            Err(e) => match Self::read_backup_toc(&mut reader, options) {
+                Ok(Some((trailer, toc))) => {
+                    log::warn!("Failed to read table of contents ({e}), using backup copy");
+                    (trailer, toc, TocCopy::Backup)
+                }
+                _ => return Err(e),
+            },
+        };
+
+        Ok(Self {
+            toc,
+            app_id: trailer.extension.app_id,
+            path: None,
+            toc_copy,
+            toc_encoding: trailer.extension.toc_encoding,
+            redundant_toc: toc_copy == TocCopy::Backup || trailer.extension.backup_end.is_some(),
+            progress: None,
+        })
+    }
+
+    /// Reads the trailer (or the backup trailer ending at the given position),
+    /// and the table of contents it points to.
+    fn read_toc<R: Read + Seek>(
+        reader: &mut R,
+        options: &ReaderOptions,
+        backup_end: Option<u64>,
+    ) -> crate::Result<(ParsedTrailer, Toc)> {
+        let trailer = match backup_end {
+            Some(end) => TrailerReader::backup_from_reader(reader, end)?,
+            None => TrailerReader::from_reader(reader)?,
+        };
+        options.check(&trailer)?;
+
+        let toc = TocReader::from_reader(reader, &trailer)?;
+
+        Ok((trailer, toc))
+    }
+
+    /// Reads the backup copy of the table of contents, if any.
+    fn read_backup_toc<R: Read + Seek>(
+        reader: &mut R,
+        options: &ReaderOptions,
+    ) -> crate::Result<Option<(ParsedTrailer, Toc)>> {
+        for end in TrailerReader::backup_ends(reader)? {
+            log::debug!("Trying backup table of contents ending at {end}");
+
+            if let Ok(result) = Self::read_toc(reader, options, Some(end)) {
+                return Ok(Some(result));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Creates a new [`Reader`] from the summary of a freshly written archive
+    /// (see [`crate::Writer::finish`]), without reading anything.
+    ///
+    /// The summary is trusted to match the archive; use [`Reader::from_summary_checked`]
+    /// or [`Reader::open_with_summary`] to validate it against the file.
+    #[must_use]
+    pub fn from_summary(summary: crate::WriteSummary) -> Self {
+        Self {
+            toc: summary.toc,
+            app_id: summary.app_id,
+            path: None,
+            toc_copy: TocCopy::Primary,
+            toc_encoding: summary.toc_encoding,
+            redundant_toc: summary.redundant_toc,
+            progress: None,
+        }
+    }
+
+    /// Creates a new [`Reader`] from the summary of a freshly written archive
+    /// (see [`crate::Writer::finish`]).
+    ///
+    /// Only the trailer is read, to check that the file ends with the table of contents
+    /// described by the summary; the table of contents itself is not read again.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, the trailer is invalid,
+    /// or the archive does not match the summary.
+    pub fn from_summary_checked<R: Read + Seek>(
+        reader: &mut R,
+        summary: crate::WriteSummary,
+    ) -> crate::Result<Self> {
+        let trailer = TrailerReader::from_reader(reader)?;
+        let file_size = reader.seek(std::io::SeekFrom::End(0))?;
+
+        if file_size != summary.file_size
+            || trailer.toc_pos != summary.toc_pos
+            || trailer.toc_len != summary.toc_len
+            || trailer.toc_checksum != summary.toc_checksum
+            || trailer.extension.app_id != summary.app_id
+            || trailer.extension.toc_encoding != summary.toc_encoding
+        {
+            log::error!("Archive does not match the write summary");
+            return Err(crate::Error::SummaryMismatch {
+                context: ErrorContext::at(trailer.trailer_pos),
+            });
+        }
+
+        Ok(Self::from_summary(summary))
+    }
+
+    /// Opens an archive from a file path, using the summary of a freshly written archive
+    /// (see [`crate::Writer::finish`]).
+    ///
+    /// Like [`Reader::from_summary_checked`], but the reader remembers the path,
+    /// which is required by some operations (e.g. [`Reader::extract_to`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, the trailer is invalid,
+    /// or the archive does not match the summary.
+    pub fn open_with_summary(
+        path: impl AsRef<std::path::Path>,
+        summary: crate::WriteSummary,
+    ) -> crate::Result<Self> {
+        let path = path.as_ref();
+
+        let file = std::fs::File::open(path)?;
+        let mut file = BufReader::new(file);
+
+        let mut reader =
+            Self::from_summary_checked(&mut file, summary).map_err(|e| e.with_path(path))?;
+        reader.path = Some(path.to_path_buf());
+
+        Ok(reader)
+    }
+
+    /// Reports the sections and bytes read by [`Reader::extract_to`] and the verification
+    /// routines (e.g. [`Reader::content_digest`] or `Reader::par_verify`) to the given observer.
+    ///
+    /// Data shared by multiple sections (see [`crate::Writer::use_deduplication`])
+    /// may only be reported once when verifying.
+    #[must_use]
+    pub fn use_progress(mut self, progress: Arc<dyn Progress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Returns which copy of the table of contents was read.
+    ///
+    /// This is [`TocCopy::Backup`] if the archive has a backup copy
+    /// (see [`crate::Writer::use_redundant_toc`]), and the primary table of contents
+    /// or trailer is corrupted, which is a good reason to rewrite the archive
+    /// (e.g. using [`crate::ArchiveEditor`]).
+    #[must_use]
+    pub fn toc_copy(&self) -> TocCopy {
+        self.toc_copy
+    }
+
+    /// Repairs damaged data of an archive, using its parity section
+    /// (see [`crate::Writer::use_parity`]).
+    ///
+    /// If all sections (including the header) have checksums (see [`crate::Writer::use_section_checksums`]),
+    /// they are used to locate the damaged sections, and only the stripes covering them are checked.
+    /// Otherwise, every stripe is checked. Damaged blocks are then located using the block
+    /// checksums stored in the parity section, and reconstructed in place.
+    ///
+    /// The table of contents and trailer are not protected by the parity
+    /// (see [`crate::Writer::use_redundant_toc`]).
+    ///
+    /// Returns the number of repaired blocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, the archive has no parity section,
+    /// or a stripe has more damaged blocks than it has parity blocks.
+    #[cfg(feature = "parity")]
+    pub fn repair(path: impl AsRef<std::path::Path>) -> crate::Result<usize> {
+        let path = path.as_ref();
+        let reader = Self::new(path)?;
+
+        let mut file = std::fs::File::options().read(true).write(true).open(path)?;
+        crate::parity::repair(&mut file, reader.toc()).map_err(|e| e.with_path(path))
     }
 
     /// Lists the table of contents.
@@ -43,4 +340,224 @@ impl Reader {
     pub fn toc(&self) -> &Toc {
         &self.toc
     }
+
+    /// Returns the archive header, if it has one.
+    ///
+    /// See [`crate::Writer::write_header`].
+    #[must_use]
+    pub fn header(&self) -> Option<&TocEntry> {
+        self.toc.header()
+    }
+
+    /// Returns the application ID, if the archive has one.
+    ///
+    /// See [`crate::Writer::use_app_id`].
+    #[must_use]
+    pub fn app_id(&self) -> Option<AppId> {
+        self.app_id.map(|(app_id, _)| app_id)
+    }
+
+    /// Returns the application-specific format version, if the archive has an application ID.
+    ///
+    /// See [`crate::Writer::use_app_id`].
+    #[must_use]
+    pub fn app_version(&self) -> Option<u32> {
+        self.app_id.map(|(_, version)| version)
+    }
+
+    /// Verifies the archive signature (see [`crate::Writer::finish_signed`]).
+    ///
+    /// This reads the entire archive, to check that no section was modified.
+    ///
+    /// The archive must have been opened using a path, otherwise use
+    /// [`Reader::verify_signature_with_reader`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, the archive is not signed,
+    /// or the signature is invalid.
+    #[cfg(feature = "signing")]
+    pub fn verify_signature(&self, public_key: &crate::VerifyingKey) -> crate::Result<()> {
+        let (path, file) = self.open_path()?;
+        let mut file = BufReader::new(file);
+
+        crate::signing::verify(&mut file, public_key, self.progress.as_deref())
+            .map_err(|e| e.with_path(path))
+    }
+
+    /// Verifies the archive signature (see [`crate::Writer::finish_signed`]),
+    /// reading the archive from the given reader.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, the archive is not signed,
+    /// or the signature is invalid.
+    #[cfg(feature = "signing")]
+    pub fn verify_signature_with_reader<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        public_key: &crate::VerifyingKey,
+    ) -> crate::Result<()> {
+        crate::signing::verify(reader, public_key, self.progress.as_deref())
+    }
+
+    /// Computes the content digest of the archive (see [`crate::ContentDigest`]).
+    ///
+    /// This reads the entire archive.
+    ///
+    /// The archive must have been opened using a path, otherwise use
+    /// [`Reader::content_digest_with_reader`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn content_digest(&self) -> crate::Result<crate::ContentDigest> {
+        let (_, file) = self.open_path()?;
+        let mut file = BufReader::new(file);
+
+        crate::ContentDigest::from_reader(&mut file, &self.toc, self.progress.as_deref())
+    }
+
+    /// Computes the content digest of the archive (see [`crate::ContentDigest`]),
+    /// reading the archive from the given reader.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn content_digest_with_reader<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+    ) -> crate::Result<crate::ContentDigest> {
+        crate::ContentDigest::from_reader(reader, &self.toc, self.progress.as_deref())
+    }
+
+    fn open_path(&self) -> crate::Result<(&std::path::Path, std::fs::File)> {
+        let Some(path) = &self.path else {
+            return Err(crate::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "archive was not opened using a path",
+            )));
+        };
+
+        Ok((path, std::fs::File::open(path)?))
+    }
+
+    /// Reads all sections (including the header) concurrently, and verifies their
+    /// checksums (see [`crate::Writer::use_section_checksums`]), if they have any.
+    ///
+    /// The archive must have been opened using a path, otherwise use
+    /// [`Reader::par_verify_with_file`].
+    ///
+    /// # Errors
+    ///
+    /// Returns all errors that occurred (not just the first one),
+    /// e.g. checksum mismatches or IO errors.
+    #[cfg(feature = "rayon")]
+    pub fn par_verify(&self) -> Result<(), Vec<crate::Error>> {
+        let (path, file) = self.open_path().map_err(|e| vec![e])?;
+
+        self.par_verify_with_file(&file)
+            .map_err(|errors| errors.into_iter().map(|e| e.with_path(path)).collect())
+    }
+
+    /// Reads all sections (including the header) concurrently, and verifies their
+    /// checksums (see [`crate::Writer::use_section_checksums`]), if they have any,
+    /// reading the archive from the given file.
+    ///
+    /// # Errors
+    ///
+    /// Returns all errors that occurred (not just the first one),
+    /// e.g. checksum mismatches or IO errors.
+    #[cfg(feature = "rayon")]
+    pub fn par_verify_with_file(&self, file: &std::fs::File) -> Result<(), Vec<crate::Error>> {
+        let errors = crate::par::verify(file, &self.toc, self.progress.as_deref());
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Calls the given function for all sections concurrently.
+    ///
+    /// Each call gets a [`crate::SectionReader`], which reads the section
+    /// using positional reads on a shared file handle. The header is not included.
+    ///
+    /// The archive must have been opened using a path, otherwise use
+    /// [`Reader::par_for_each_section_with_file`].
+    ///
+    /// # Errors
+    ///
+    /// Returns all errors that occurred (not just the first one).
+    #[cfg(feature = "rayon")]
+    pub fn par_for_each_section<F>(&self, f: F) -> Result<(), Vec<crate::Error>>
+    where
+        F: Fn(&TocEntry, &mut crate::SectionReader<'_>) -> crate::Result<()> + Sync,
+    {
+        let (path, file) = self.open_path().map_err(|e| vec![e])?;
+
+        self.par_for_each_section_with_file(&file, f)
+            .map_err(|errors| errors.into_iter().map(|e| e.with_path(path)).collect())
+    }
+
+    /// Calls the given function for all sections concurrently,
+    /// reading the archive from the given file (see [`Reader::par_for_each_section`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns all errors that occurred (not just the first one).
+    #[cfg(feature = "rayon")]
+    pub fn par_for_each_section_with_file<F>(
+        &self,
+        file: &std::fs::File,
+        f: F,
+    ) -> Result<(), Vec<crate::Error>>
+    where
+        F: Fn(&TocEntry, &mut crate::SectionReader<'_>) -> crate::Result<()> + Sync,
+    {
+        let errors = crate::par::for_each_section(file, &self.toc, f);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Extracts all sections into files in the given directory.
+    ///
+    /// Section names are treated as `/`-separated relative paths, and parent
+    /// directories are created as needed. The header and unnamed sections (e.g. data written
+    /// before the first named section) are not extracted.
+    ///
+    /// Before anything is written, all section names are checked to be safe:
+    /// absolute paths, `.` and `..` components, backslashes and NUL bytes are refused.
+    /// Symbolic links inside the directory are never followed, so an existing link
+    /// cannot redirect files outside of it.
+    ///
+    /// The archive must have been opened using a path.
+    ///
+    /// Returns the number of extracted files.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, a section name is unsafe,
+    /// a file already exists (depending on [`crate::OnCollision`]), or a checksum does not match.
+    pub fn extract_to(
+        &self,
+        dir: impl AsRef<std::path::Path>,
+        options: &crate::ExtractOptions,
+    ) -> crate::Result<usize> {
+        let (path, file) = self.open_path()?;
+
+        crate::extract::extract_to(
+            file,
+            &self.toc,
+            dir.as_ref(),
+            options,
+            self.progress.as_deref(),
+        )
+        .map_err(|e| e.with_path(path))
+    }
 }
@@ -0,0 +1,81 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    detect::{read_leading_magic, LEADING_MAGIC_SIZE},
+    frame::read_frame,
+    ErrorContext, Reader, WriteSummary, Writer,
+};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Seek, SeekFrom},
+    path::Path,
+};
+
+/// Recovers an archive that was not completely written (e.g. because the process crashed),
+/// by rebuilding its table of contents from the section frames
+/// (see [`Writer::use_section_frames`]).
+///
+/// All complete sections (whose frames and checksums are intact) up to the first incomplete one
+/// are kept; the file is truncated after them, and a new table of contents and trailer are written.
+/// The table of contents entries keep their flag-dependent fields (e.g. encryption parameters
+/// and attributes), but archive-wide options stored in the trailer (e.g. the application ID
+/// and compact table of contents encoding) and signatures are not restored.
+///
+/// Returns a summary of the recovered archive, or `None` if the archive is intact,
+/// in which case it is left untouched.
+///
+/// # Errors
+///
+/// Returns error, if an IO error occurred, or the file does not contain any complete section frames.
+pub fn recover(path: impl AsRef<Path>) -> crate::Result<Option<WriteSummary>> {
+    let path = path.as_ref();
+    recover_inner(path).map_err(|e| e.with_path(path))
+}
+
+fn recover_inner(path: &Path) -> crate::Result<Option<WriteSummary>> {
+    if Reader::new(path).is_ok() {
+        log::debug!("Archive {} is intact, not recovering", path.display());
+        return Ok(None);
+    }
+
+    let mut file = File::options().read(true).write(true).open(path)?;
+
+    let mut pos = if read_leading_magic(&mut file)?.is_some() {
+        LEADING_MAGIC_SIZE as u64
+    } else {
+        0
+    };
+
+    let mut sections = vec![];
+
+    {
+        let mut reader = BufReader::new(&mut file);
+
+        while let Some((entry, checksum, next_pos)) = read_frame(&mut reader, pos)? {
+            sections.push((entry, checksum));
+            pos = next_pos;
+        }
+    }
+
+    if sections.is_empty() {
+        return Err(crate::Error::NoSectionFrames {
+            context: ErrorContext::at(pos),
+        });
+    }
+
+    log::debug!(
+        "Recovered {} sections, truncating {} to {pos} bytes",
+        sections.len(),
+        path.display(),
+    );
+
+    file.set_len(pos)?;
+    file.seek(SeekFrom::Start(pos))?;
+
+    let summary = Writer::from_recovered(BufWriter::new(&mut file), sections, pos).finish()?;
+    file.sync_all()?;
+
+    Ok(Some(summary))
+}
@@ -0,0 +1,234 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    toc::reader::TocReader,
+    trailer::{reader::TrailerReader, writer::TRAILER_SIZE},
+    ErrorContext, Progress,
+};
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// Magic bytes at the start of the signature block
+pub const SIGNATURE_MAGIC: &[u8] = b"SIG!";
+
+/// Ed25519
+const ALGORITHM_ED25519: u8 = 0x0;
+
+/// Domain separation, so signatures cannot be confused with other messages signed by the same key
+const SIGNATURE_CONTEXT: &[u8] = b"sfa signature v1";
+
+pub type SectionDigest = [u8; 32];
+
+/// Builds the signed message
+///
+/// The trailer commits to the table of contents checksum, but because that is not
+/// a cryptographic hash, the message also includes a SHA-256 digest of the
+/// table of contents itself.
+fn message(
+    toc_digest: &SectionDigest,
+    raw_extension: &[u8],
+    raw_trailer: &[u8],
+    section_digests: &[SectionDigest],
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(
+        SIGNATURE_CONTEXT.len()
+            + 32
+            + raw_extension.len()
+            + raw_trailer.len()
+            + section_digests.len() * 32,
+    );
+
+    message.extend_from_slice(SIGNATURE_CONTEXT);
+    message.extend_from_slice(toc_digest);
+    message.extend_from_slice(raw_extension);
+    message.extend_from_slice(raw_trailer);
+
+    for digest in section_digests {
+        message.extend_from_slice(digest);
+    }
+
+    message
+}
+
+/// Writes the signature block, which is placed between the table of contents
+/// and the trailer, where older readers ignore it.
+pub fn write_signature_block<W: Write>(
+    mut writer: W,
+    key: &SigningKey,
+    toc_digest: &SectionDigest,
+    raw_extension: &[u8],
+    raw_trailer: &[u8],
+    section_digests: &[SectionDigest],
+) -> crate::Result<()> {
+    use byteorder::LE;
+
+    log::trace!("Writing signature block");
+
+    let signature = key.sign(&message(
+        toc_digest,
+        raw_extension,
+        raw_trailer,
+        section_digests,
+    ));
+
+    writer.write_all(SIGNATURE_MAGIC)?;
+    writer.write_u8(ALGORITHM_ED25519)?;
+
+    #[allow(clippy::expect_used)]
+    writer.write_u32::<LE>(
+        u32::try_from(section_digests.len()).expect("should not have more than 4 billion sections"),
+    )?;
+
+    for digest in section_digests {
+        writer.write_all(digest)?;
+    }
+
+    writer.write_all(&signature.to_bytes())?;
+
+    Ok(())
+}
+
+fn hash_range<R: Read + Seek>(
+    reader: &mut R,
+    pos: u64,
+    len: u64,
+    progress: Option<&dyn Progress>,
+) -> std::io::Result<SectionDigest> {
+    reader.seek(SeekFrom::Start(pos))?;
+
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 64 * 1_024];
+    let mut remaining = len;
+
+    while remaining > 0 {
+        #[allow(clippy::cast_possible_truncation)]
+        let n = remaining.min(buf.len() as u64) as usize;
+
+        let buf = buf.get_mut(..n).unwrap_or_default();
+        reader.read_exact(buf)?;
+        hasher.update(&*buf);
+
+        if let Some(progress) = progress {
+            progress.on_bytes(n as u64);
+        }
+
+        remaining -= n as u64;
+    }
+
+    Ok(hasher.finalize().into())
+}
+
+/// Verifies the signature block and all section digests of an archive.
+pub fn verify<R: Read + Seek>(
+    reader: &mut R,
+    key: &VerifyingKey,
+    progress: Option<&dyn Progress>,
+) -> crate::Result<()> {
+    use byteorder::LE;
+
+    log::trace!("Verifying archive signature");
+
+    let trailer = TrailerReader::from_reader(reader)?;
+    let toc = TocReader::from_reader(reader, &trailer)?;
+
+    let block_pos = trailer.toc_pos + trailer.toc_len;
+    let context = ErrorContext::at(block_pos);
+
+    let block_len = trailer.trailer_pos.saturating_sub(block_pos);
+
+    if block_len < (SIGNATURE_MAGIC.len() + 1 + 4) as u64 {
+        log::error!("Archive is not signed");
+        return Err(crate::Error::MissingSignature { context });
+    }
+
+    reader.seek(SeekFrom::Start(block_pos))?;
+
+    {
+        let mut buf = [0u8; SIGNATURE_MAGIC.len()];
+        reader.read_exact(&mut buf)?;
+
+        if buf != SIGNATURE_MAGIC {
+            log::error!("Archive is not signed");
+            return Err(crate::Error::MissingSignature { context });
+        }
+    }
+
+    let algorithm = reader.read_u8()?;
+    let count = reader.read_u32::<LE>()?;
+
+    let entries = toc
+        .header()
+        .into_iter()
+        .chain(toc.iter())
+        .collect::<Vec<_>>();
+
+    if algorithm != ALGORITHM_ED25519
+        || count as usize != entries.len()
+        || block_len
+            != (SIGNATURE_MAGIC.len() + 1 + 4) as u64
+                + u64::from(count) * 32
+                + Signature::BYTE_SIZE as u64
+    {
+        log::error!("Invalid signature block");
+        return Err(crate::Error::InvalidSignature { context });
+    }
+
+    let mut section_digests = Vec::with_capacity(entries.len());
+
+    for _ in 0..count {
+        let mut digest = [0; 32];
+        reader.read_exact(&mut digest)?;
+        section_digests.push(digest);
+    }
+
+    let mut signature = [0; Signature::BYTE_SIZE];
+    reader.read_exact(&mut signature)?;
+    let signature = Signature::from_bytes(&signature);
+
+    #[allow(clippy::cast_possible_truncation)]
+    let mut raw_trailer = [0; TRAILER_SIZE.unsigned_abs() as usize];
+    reader.seek(SeekFrom::End(-TRAILER_SIZE))?;
+    reader.read_exact(&mut raw_trailer)?;
+
+    let toc_digest = hash_range(reader, trailer.toc_pos, trailer.toc_len, None)?;
+
+    let message = message(
+        &toc_digest,
+        &trailer.raw_extension,
+        &raw_trailer,
+        &section_digests,
+    );
+
+    if key.verify_strict(&message, &signature).is_err() {
+        log::error!("Invalid archive signature");
+        return Err(crate::Error::InvalidSignature { context });
+    }
+
+    // NOTE: The signature is valid, so now we only need to check that the sections
+    // have not been modified
+    for (entry, expected) in entries.into_iter().zip(&section_digests) {
+        if let Some(progress) = progress {
+            progress.on_section_start(entry.name());
+        }
+
+        if hash_range(reader, entry.pos(), entry.len(), progress)? != *expected {
+            log::error!(
+                "Section {:?} does not match its signed digest",
+                entry.name()
+            );
+            return Err(crate::Error::InvalidSignature {
+                context: ErrorContext::section(entry.name(), entry.pos()),
+            });
+        }
+
+        if let Some(progress) = progress {
+            progress.on_section_end(entry);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,257 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+//! Compact table of contents encoding
+//!
+//! Names are front-coded (the length of the prefix shared with the previous name,
+//! followed by the remaining suffix), and positions are stored as the (zigzag encoded)
+//! distance to the end of the previous section, which is 0 for contiguous sections.
+//! All integers are LEB128 varints.
+
+use super::entry::TocEntry;
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use std::io::{Read, Write};
+
+/// Magic bytes of a compact table of contents
+///
+/// Differs from the regular magic, so readers that do not understand
+/// the compact encoding fail instead of misinterpreting it.
+pub const COMPACT_TOC_MAGIC: &[u8] = b"TOC+";
+
+fn write_varint(mut writer: impl Write, mut value: u64) -> std::io::Result<()> {
+    while value >= 0x80 {
+        #[allow(clippy::cast_possible_truncation)]
+        writer.write_u8((value as u8) | 0x80)?;
+        value >>= 7;
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    writer.write_u8(value as u8)
+}
+
+fn read_varint(mut reader: impl Read) -> std::io::Result<u64> {
+    let mut value = 0;
+
+    for shift in (0..64).step_by(7) {
+        let byte = reader.read_u8()?;
+        value |= u64::from(byte & 0x7F) << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        "varint is too long",
+    ))
+}
+
+fn shared_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+#[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+#[allow(clippy::cast_possible_wrap)]
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+pub fn write_entries(mut writer: impl Write, entries: &[TocEntry]) -> crate::Result<()> {
+    let mut prev_name: &[u8] = &[];
+    let mut prev_end = 0;
+
+    for entry in entries {
+        // NOTE: Longer names could not be read back
+        if entry.name().len() > usize::from(u16::MAX) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "section name is too long",
+            )
+            .into());
+        }
+
+        let prefix_len = shared_prefix_len(prev_name, entry.name());
+
+        #[allow(clippy::indexing_slicing)]
+        let suffix = &entry.name()[prefix_len..];
+
+        write_varint(&mut writer, prefix_len as u64)?;
+        write_varint(&mut writer, suffix.len() as u64)?;
+        writer.write_all(suffix)?;
+
+        #[allow(clippy::cast_possible_wrap)]
+        let delta = entry.pos.wrapping_sub(prev_end) as i64;
+
+        write_varint(&mut writer, zigzag(delta))?;
+        write_varint(&mut writer, entry.len)?;
+
+        entry.write_flags_into(&mut writer)?;
+
+        prev_name = entry.name();
+        prev_end = entry.pos.wrapping_add(entry.len);
+    }
+
+    Ok(())
+}
+
+pub fn read_entries(mut reader: impl Read, len: u32) -> std::io::Result<Vec<TocEntry>> {
+    let invalid = |msg| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+
+    // NOTE: Don't trust the length for preallocation
+    let mut entries: Vec<TocEntry> = Vec::with_capacity((len as usize).min(1_000));
+
+    let mut prev_end = 0u64;
+
+    for _ in 0..len {
+        let prefix_len = read_varint(&mut reader)?;
+        let suffix_len = read_varint(&mut reader)?;
+
+        let prev_name = entries.last().map(TocEntry::name).unwrap_or_default();
+
+        let prefix = usize::try_from(prefix_len)
+            .ok()
+            .and_then(|prefix_len| prev_name.get(..prefix_len))
+            .ok_or_else(|| invalid("shared prefix exceeds previous section name"))?;
+
+        if prefix_len
+            .checked_add(suffix_len)
+            .is_none_or(|name_len| name_len > u64::from(u16::MAX))
+        {
+            return Err(invalid("section name is too long"));
+        }
+
+        let mut name = prefix.to_vec();
+
+        #[allow(clippy::cast_possible_truncation)]
+        name.resize(prefix.len() + suffix_len as usize, 0);
+
+        #[allow(clippy::indexing_slicing)]
+        reader.read_exact(&mut name[prefix.len()..])?;
+
+        #[allow(clippy::cast_sign_loss)]
+        let pos = prev_end.wrapping_add(unzigzag(read_varint(&mut reader)?) as u64);
+        let len = read_varint(&mut reader)?;
+
+        let mut entry = TocEntry {
+            name,
+            pos,
+            len,
+            ..Default::default()
+        };
+
+        entry.read_flags_from(&mut reader).map_err(|e| match e {
+            crate::Error::Io(e) => e,
+            _ => invalid("invalid section flags"),
+        })?;
+
+        prev_end = pos.wrapping_add(len);
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn compact_toc_varint() -> std::io::Result<()> {
+        for value in [0, 1, 127, 128, 300, u64::from(u32::MAX), u64::MAX] {
+            let mut bytes = vec![];
+            write_varint(&mut bytes, value)?;
+            assert_eq!(value, read_varint(&bytes[..])?);
+        }
+
+        assert!(read_varint(&[0xFF; 11][..]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn compact_toc_zigzag() {
+        for value in [0, 1, -1, 1_000, -1_000, i64::MAX, i64::MIN] {
+            assert_eq!(value, unzigzag(zigzag(value)));
+        }
+    }
+
+    #[test]
+    fn compact_toc_roundtrip() -> crate::Result<()> {
+        let entries = vec![
+            TocEntry {
+                name: b"idx/a".to_vec(),
+                pos: 12,
+                len: 100,
+                ..Default::default()
+            },
+            TocEntry {
+                name: b"idx/ab".to_vec(),
+                pos: 112,
+                len: 5,
+                ..Default::default()
+            },
+            TocEntry {
+                name: b"data".to_vec(),
+                pos: 50,
+                len: 0,
+                ..Default::default()
+            },
+        ];
+
+        let mut bytes = vec![];
+        write_entries(&mut bytes, &entries)?;
+
+        let decoded = read_entries(&bytes[..], 3)?;
+        assert_eq!(3, decoded.len());
+
+        for (a, b) in entries.iter().zip(&decoded) {
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.pos, b.pos);
+            assert_eq!(a.len, b.len);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn compact_toc_invalid_prefix() -> std::io::Result<()> {
+        let mut bytes = vec![];
+        write_varint(&mut bytes, 1)?;
+        write_varint(&mut bytes, 0)?;
+
+        assert!(read_entries(&bytes[..], 1).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn compact_toc_name_len_overflow() -> crate::Result<()> {
+        let mut bytes = vec![];
+        write_entries(
+            &mut bytes,
+            &[TocEntry {
+                name: b"a".to_vec(),
+                ..Default::default()
+            }],
+        )?;
+
+        // NOTE: Forged suffix length, which overflows when added to the prefix length
+        write_varint(&mut bytes, 1)?;
+        write_varint(&mut bytes, u64::MAX)?;
+
+        assert_eq!(
+            std::io::ErrorKind::InvalidData,
+            read_entries(&bytes[..], 2).unwrap_err().kind()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use super::{entry::TocEntry, Toc};
+use std::{cmp::Ordering, collections::BTreeSet};
+
+/// Separator of path components in section names
+pub const SEPARATOR: u8 = b'/';
+
+/// Entry of a directory-style listing (see [`Toc::list_dir`] and [`Toc::walk`])
+///
+/// Directories are not stored in the archive, but implied by section names
+/// that contain a `/`.
+#[derive(Copy, Clone, Debug)]
+pub enum DirEntry<'a> {
+    /// Directory
+    Dir {
+        /// Full path of the directory, without a trailing `/`
+        path: &'a [u8],
+    },
+
+    /// Section
+    Section(&'a TocEntry),
+}
+
+impl<'a> DirEntry<'a> {
+    /// Returns the full path of the entry.
+    #[must_use]
+    pub fn path(&self) -> &'a [u8] {
+        match self {
+            Self::Dir { path } => path,
+            Self::Section(entry) => entry.name(),
+        }
+    }
+
+    /// Returns the last path component of the entry.
+    #[must_use]
+    pub fn file_name(&self) -> &'a [u8] {
+        let path = self.path();
+
+        match path.iter().rposition(|&b| b == SEPARATOR) {
+            Some(idx) => path.get(idx + 1..).unwrap_or_default(),
+            None => path,
+        }
+    }
+
+    /// Returns the amount of directories the entry is nested in.
+    #[must_use]
+    #[allow(clippy::naive_bytecount)]
+    pub fn depth(&self) -> usize {
+        self.path().iter().filter(|&&b| b == SEPARATOR).count()
+    }
+
+    /// Returns `true` if the entry is a directory.
+    #[must_use]
+    pub fn is_dir(&self) -> bool {
+        matches!(self, Self::Dir { .. })
+    }
+}
+
+/// Compares names component by component, so that the contents
+/// of a directory are sorted directly after the directory itself.
+fn cmp_components(a: &[u8], b: &[u8]) -> Ordering {
+    a.split(|&b| b == SEPARATOR)
+        .cmp(b.split(|&b| b == SEPARATOR))
+}
+
+/// Checks that a section name is a normalized relative path.
+///
+/// Returns a description of the problem, if it is not.
+pub fn validate_path_name(name: &[u8]) -> Result<(), &'static str> {
+    if name.is_empty() {
+        return Err("name is empty");
+    }
+
+    if name.contains(&b'\\') || name.contains(&0) {
+        return Err("name contains a backslash or NUL byte");
+    }
+
+    for component in name.split(|&b| b == SEPARATOR) {
+        match component {
+            b"" => return Err("name contains an empty path component"),
+            b"." | b".." => return Err("name contains a relative path component"),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+impl Toc {
+    /// Returns all sections whose names start with the given prefix, in archive order.
+    pub fn prefix<'a>(&'a self, prefix: &'a [u8]) -> impl Iterator<Item = &'a TocEntry> + 'a {
+        self.iter()
+            .filter(move |entry| entry.name().starts_with(prefix))
+    }
+
+    /// Lists the direct children of a directory, sorted by name.
+    ///
+    /// Section names are treated as `/`-separated paths. The directory may be
+    /// given with or without a trailing `/`, and an empty directory lists the root.
+    ///
+    /// ```
+    /// # use sfa::{Reader, Writer};
+    /// # use std::io::Write;
+    /// # let dir = tempfile::tempdir()?;
+    /// # let path = dir.path().join("hello.sfa");
+    /// # let mut file = std::fs::File::create(&path)?;
+    /// let mut writer = Writer::from_writer(&mut file);
+    /// writer.start("a/b/c")?;
+    /// writer.start("a/b/d/e")?;
+    /// writer.start("a/f")?;
+    /// writer.finish()?;
+    /// # drop(file);
+    ///
+    /// let reader = Reader::new(&path)?;
+    /// let names = reader
+    ///     .toc()
+    ///     .list_dir(b"a/b/")
+    ///     .map(|entry| entry.file_name())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(vec![&b"c"[..], b"d"], names);
+    /// #
+    /// # Ok::<(), sfa::Error>(())
+    /// ```
+    pub fn list_dir<'a>(&'a self, dir: &[u8]) -> impl Iterator<Item = DirEntry<'a>> + 'a {
+        let dir = dir.strip_suffix(&[SEPARATOR]).unwrap_or(dir);
+
+        let mut dirs = BTreeSet::new();
+        let mut sections = vec![];
+
+        for entry in self.iter() {
+            let rest = if dir.is_empty() {
+                Some(entry.name())
+            } else {
+                entry
+                    .name()
+                    .strip_prefix(dir)
+                    .and_then(|rest| rest.strip_prefix(&[SEPARATOR]))
+            };
+
+            let Some(rest) = rest else {
+                continue;
+            };
+
+            match rest.iter().position(|&b| b == SEPARATOR) {
+                Some(idx) => {
+                    let len = entry.name().len() - rest.len() + idx;
+                    dirs.insert(entry.name().get(..len).unwrap_or_default());
+                }
+                None => sections.push(entry),
+            }
+        }
+
+        let mut entries = dirs
+            .into_iter()
+            .map(|path| DirEntry::Dir { path })
+            .chain(sections.into_iter().map(DirEntry::Section))
+            .collect::<Vec<_>>();
+
+        entries.sort_by(|a, b| cmp_components(a.path(), b.path()));
+
+        entries.into_iter()
+    }
+
+    /// Recursively lists all directories and sections, depth-first and sorted by name.
+    ///
+    /// Every directory is listed once, directly before its contents.
+    pub fn walk(&self) -> impl Iterator<Item = DirEntry<'_>> {
+        let mut sections = self.iter().collect::<Vec<_>>();
+        sections.sort_by(|a, b| cmp_components(a.name(), b.name()));
+
+        let mut entries = Vec::with_capacity(sections.len());
+
+        // Directories of the previous section, from the outermost to the innermost
+        let mut open_dirs: Vec<&[u8]> = vec![];
+
+        for section in sections {
+            let name = section.name();
+
+            let dirs = name
+                .iter()
+                .enumerate()
+                .filter(|(_, &b)| b == SEPARATOR)
+                .map(|(idx, _)| name.get(..idx).unwrap_or_default())
+                .collect::<Vec<_>>();
+
+            let shared = open_dirs
+                .iter()
+                .zip(&dirs)
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            open_dirs.truncate(shared);
+
+            for &path in dirs.get(shared..).unwrap_or_default() {
+                entries.push(DirEntry::Dir { path });
+                open_dirs.push(path);
+            }
+
+            entries.push(DirEntry::Section(section));
+        }
+
+        entries.into_iter()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    fn toc(names: &[&str]) -> Toc {
+        Toc::new(
+            names
+                .iter()
+                .map(|name| TocEntry {
+                    name: name.as_bytes().to_vec(),
+                    pos: 0,
+                    len: 0,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    fn paths<'a>(entries: impl Iterator<Item = DirEntry<'a>>) -> Vec<String> {
+        entries
+            .map(|entry| {
+                let path = String::from_utf8_lossy(entry.path()).into_owned();
+                if entry.is_dir() {
+                    format!("{path}/")
+                } else {
+                    path
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn toc_list_dir() {
+        let toc = toc(&["a/b/c", "x", "a/b/d/e", "a/f", "a/b/d/g", "ab"]);
+
+        assert_eq!(vec!["a/", "ab", "x"], paths(toc.list_dir(b"")));
+        assert_eq!(vec!["a/b/", "a/f"], paths(toc.list_dir(b"a")));
+        assert_eq!(vec!["a/b/c", "a/b/d/"], paths(toc.list_dir(b"a/b/")));
+        assert!(toc.list_dir(b"a/b/c").next().is_none());
+        assert!(toc.list_dir(b"nope").next().is_none());
+    }
+
+    #[test]
+    fn toc_walk() {
+        let toc = toc(&["a/b/c", "x", "a/b/d/e", "a/f", "a/b/d/g", "a.b", "y/z/w"]);
+
+        assert_eq!(
+            vec![
+                "a/", "a/b/", "a/b/c", "a/b/d/", "a/b/d/e", "a/b/d/g", "a/f", "a.b", "x", "y/",
+                "y/z/", "y/z/w",
+            ],
+            paths(toc.walk()),
+        );
+
+        let depths = toc.walk().map(|entry| entry.depth()).collect::<Vec<_>>();
+        assert_eq!(vec![0, 1, 2, 2, 3, 3, 1, 0, 0, 0, 1, 2], depths);
+    }
+
+    #[test]
+    fn toc_prefix() {
+        let toc = toc(&["idx/a", "data/a", "idx/b", "idx"]);
+
+        let names = toc.prefix(b"idx/").map(TocEntry::name).collect::<Vec<_>>();
+
+        assert_eq!(vec![b"idx/a", b"idx/b"], names);
+    }
+
+    #[test]
+    fn toc_validate_path_name() {
+        assert!(validate_path_name(b"a").is_ok());
+        assert!(validate_path_name(b"a/b.txt").is_ok());
+        assert!(validate_path_name(b"..a/b..").is_ok());
+
+        assert!(validate_path_name(b"").is_err());
+        assert!(validate_path_name(b"/a").is_err());
+        assert!(validate_path_name(b"a/").is_err());
+        assert!(validate_path_name(b"a//b").is_err());
+        assert!(validate_path_name(b"a/../b").is_err());
+        assert!(validate_path_name(b"./a").is_err());
+        assert!(validate_path_name(b"a\\b").is_err());
+        assert!(validate_path_name(b"a\0b").is_err());
+    }
+}
@@ -8,16 +8,230 @@ use std::{
     fs::File,
     io::{BufReader, Read, Seek, Write},
     path::Path,
+    time::{Duration, SystemTime},
 };
 
+use crate::{version::FormatVersion, Checksum};
+
 pub type SectionName = Vec<u8>;
 
+/// Marks the header region before the first named section
+pub const FLAG_HEADER: u8 = 0b0000_0001;
+
+/// Marks an encrypted section, followed by its [`EncryptionInfo`]
+pub const FLAG_ENCRYPTED: u8 = 0b0000_0010;
+
+/// Marks a section with a checksum, followed by the checksum (16 bytes)
+pub const FLAG_CHECKSUM: u8 = 0b0000_0100;
+
+/// Marks a section with file attributes, followed by its [`SectionAttributes`]
+pub const FLAG_ATTRIBUTES: u8 = 0b0000_1000;
+
+/// Marks the parity section, which is not part of the sections
+pub const FLAG_PARITY: u8 = 0b0001_0000;
+
+/// Marks a section of a multi-volume archive, followed by its volume index (4 bytes)
+pub const FLAG_VOLUME: u8 = 0b0010_0000;
+
+/// Encryption parameters of an encrypted section
+///
+/// Encrypted sections are split into chunks of (at most) `chunk_size` plaintext bytes,
+/// which are encrypted and authenticated individually, so they can be accessed randomly.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EncryptionInfo {
+    /// AEAD algorithm, `0x0` = XChaCha20-Poly1305
+    pub(crate) algorithm: u8,
+
+    /// User-defined ID of the key the section was encrypted with
+    pub(crate) key_id: u32,
+
+    /// Plaintext bytes per chunk
+    pub(crate) chunk_size: u32,
+
+    /// Random nonce prefix, unique per section
+    pub(crate) nonce_prefix: [u8; 16],
+
+    /// Section length before encryption
+    pub(crate) plaintext_len: u64,
+}
+
+impl EncryptionInfo {
+    /// Returns the user-defined ID of the key the section was encrypted with.
+    #[must_use]
+    pub fn key_id(&self) -> u32 {
+        self.key_id
+    }
+
+    /// Returns the amount of plaintext bytes per chunk.
+    #[must_use]
+    pub fn chunk_size(&self) -> u32 {
+        self.chunk_size
+    }
+
+    /// Returns the section length before encryption.
+    #[must_use]
+    pub fn plaintext_len(&self) -> u64 {
+        self.plaintext_len
+    }
+
+    fn write_into(&self, mut writer: impl Write) -> crate::Result<()> {
+        use byteorder::LE;
+
+        writer.write_u8(self.algorithm)?;
+        writer.write_u32::<LE>(self.key_id)?;
+        writer.write_u32::<LE>(self.chunk_size)?;
+        writer.write_all(&self.nonce_prefix)?;
+        writer.write_u64::<LE>(self.plaintext_len)?;
+
+        Ok(())
+    }
+
+    fn read_from_file(reader: &mut impl Read) -> crate::Result<Self> {
+        use byteorder::LE;
+
+        let algorithm = reader.read_u8()?;
+        let key_id = reader.read_u32::<LE>()?;
+        let chunk_size = reader.read_u32::<LE>()?;
+
+        let mut nonce_prefix = [0; 16];
+        reader.read_exact(&mut nonce_prefix)?;
+
+        let plaintext_len = reader.read_u64::<LE>()?;
+
+        Ok(Self {
+            algorithm,
+            key_id,
+            chunk_size,
+            nonce_prefix,
+            plaintext_len,
+        })
+    }
+}
+
+/// File attributes of a section, which are restored when extracting the archive
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct SectionAttributes {
+    mode: Option<u32>,
+    mtime: Option<SystemTime>,
+}
+
+impl SectionAttributes {
+    const HAS_MODE: u8 = 0b01;
+    const HAS_MTIME: u8 = 0b10;
+
+    /// Sets the Unix file mode (permission bits).
+    #[must_use]
+    pub fn with_mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Sets the modification time.
+    #[must_use]
+    pub fn with_mtime(mut self, mtime: SystemTime) -> Self {
+        self.mtime = Some(mtime);
+        self
+    }
+
+    pub(crate) fn without_mtime(mut self) -> Self {
+        self.mtime = None;
+        self
+    }
+
+    /// Returns the Unix file mode (permission bits), if recorded.
+    #[must_use]
+    pub fn mode(&self) -> Option<u32> {
+        self.mode
+    }
+
+    /// Returns the modification time, if recorded.
+    #[must_use]
+    pub fn mtime(&self) -> Option<SystemTime> {
+        self.mtime
+    }
+
+    fn write_into(&self, mut writer: impl Write) -> crate::Result<()> {
+        use byteorder::LE;
+
+        let mut present = 0;
+        if self.mode.is_some() {
+            present |= Self::HAS_MODE;
+        }
+        if self.mtime.is_some() {
+            present |= Self::HAS_MTIME;
+        }
+        writer.write_u8(present)?;
+
+        if let Some(mode) = self.mode {
+            writer.write_u32::<LE>(mode)?;
+        }
+
+        if let Some(mtime) = self.mtime {
+            // NOTE: Seconds are signed, so times before the Unix epoch can be stored
+            let (secs, nanos) = match mtime.duration_since(SystemTime::UNIX_EPOCH) {
+                Ok(d) => (
+                    i64::try_from(d.as_secs()).unwrap_or(i64::MAX),
+                    d.subsec_nanos(),
+                ),
+                Err(e) => {
+                    let d = e.duration();
+                    let secs = i64::try_from(d.as_secs()).unwrap_or(i64::MAX);
+
+                    if d.subsec_nanos() == 0 {
+                        (-secs, 0)
+                    } else {
+                        (-secs - 1, 1_000_000_000 - d.subsec_nanos())
+                    }
+                }
+            };
+
+            writer.write_i64::<LE>(secs)?;
+            writer.write_u32::<LE>(nanos)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_from_file(reader: &mut impl Read) -> crate::Result<Self> {
+        use byteorder::LE;
+
+        let present = reader.read_u8()?;
+        let mut attributes = Self::default();
+
+        if present & Self::HAS_MODE != 0 {
+            attributes.mode = Some(reader.read_u32::<LE>()?);
+        }
+
+        if present & Self::HAS_MTIME != 0 {
+            let secs = reader.read_i64::<LE>()?;
+            let nanos = reader.read_u32::<LE>()?;
+
+            let offset = Duration::from_secs(secs.unsigned_abs());
+
+            // NOTE: Invalid times are ignored, instead of failing the entire archive
+            attributes.mtime = if secs >= 0 {
+                SystemTime::UNIX_EPOCH.checked_add(offset)
+            } else {
+                SystemTime::UNIX_EPOCH.checked_sub(offset)
+            }
+            .and_then(|time| time.checked_add(Duration::from_nanos(nanos.into())));
+        }
+
+        Ok(attributes)
+    }
+}
+
 /// Entry in the table of contents (a section in the archive)
-#[derive(Debug)]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct TocEntry {
     pub(crate) name: SectionName,
     pub(crate) pos: u64,
     pub(crate) len: u64,
+    pub(crate) flags: u8,
+    pub(crate) encryption: Option<EncryptionInfo>,
+    pub(crate) checksum: Option<Checksum>,
+    pub(crate) attributes: Option<SectionAttributes>,
+    pub(crate) volume: Option<u32>,
 }
 
 impl TocEntry {
@@ -33,13 +247,81 @@ impl TocEntry {
         self.pos
     }
 
-    /// Returns the section length in bytes.
+    /// Returns the section length in bytes, as stored in the archive.
+    ///
+    /// For encrypted sections, this is the length of the encrypted data.
     #[must_use]
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> u64 {
         self.len
     }
 
+    /// Returns `true` if the entry describes the archive header
+    /// (see [`crate::Writer::write_header`]).
+    #[must_use]
+    pub fn is_header(&self) -> bool {
+        self.flags & FLAG_HEADER != 0
+    }
+
+    /// Returns `true` if the entry describes the parity section
+    /// (see `Writer::use_parity`, requires the `parity` feature).
+    #[must_use]
+    pub fn is_parity(&self) -> bool {
+        self.flags & FLAG_PARITY != 0
+    }
+
+    /// Returns the encryption parameters, if the section is encrypted.
+    ///
+    /// Encrypted sections can be read using `DecryptingReader`
+    /// (requires the `encryption` feature).
+    #[must_use]
+    pub fn encryption(&self) -> Option<&EncryptionInfo> {
+        self.encryption.as_ref()
+    }
+
+    /// Returns the checksum of the section data (as stored in the archive), if recorded.
+    ///
+    /// See [`crate::Writer::use_section_checksums`].
+    #[must_use]
+    pub fn checksum(&self) -> Option<Checksum> {
+        self.checksum
+    }
+
+    /// Returns the file attributes, if recorded.
+    ///
+    /// See [`crate::Writer::set_attributes`].
+    #[must_use]
+    pub fn attributes(&self) -> Option<&SectionAttributes> {
+        self.attributes.as_ref()
+    }
+
+    /// Returns the index of the volume that the section starts in,
+    /// if the archive is split into volumes (see [`crate::Writer::new_multi_volume`]).
+    ///
+    /// The section position is relative to the start of that volume.
+    #[must_use]
+    pub fn volume(&self) -> Option<u32> {
+        self.volume
+    }
+
+    /// Sets the flags of the optional fields, depending on which are present.
+    pub(crate) fn update_flags(&mut self) {
+        let fields = [
+            (FLAG_ENCRYPTED, self.encryption.is_some()),
+            (FLAG_CHECKSUM, self.checksum.is_some()),
+            (FLAG_ATTRIBUTES, self.attributes.is_some()),
+            (FLAG_VOLUME, self.volume.is_some()),
+        ];
+
+        for (flag, present) in fields {
+            if present {
+                self.flags |= flag;
+            } else {
+                self.flags &= !flag;
+            }
+        }
+    }
+
     #[doc(hidden)]
     pub fn reader(&self, path: &Path) -> std::io::Result<impl std::io::Read> {
         let mut file = File::open(path)?;
@@ -47,6 +329,22 @@ impl TocEntry {
         Ok(file.take(self.len))
     }
 
+    /// Opens a reader that decrypts the section.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, the section is not encrypted,
+    /// or was encrypted with a different key.
+    #[cfg(feature = "encryption")]
+    pub fn decrypting_reader(
+        &self,
+        path: &Path,
+        key: &crate::EncryptionKey,
+    ) -> crate::Result<crate::DecryptingReader<BufReader<File>>> {
+        let file = BufReader::new(File::open(path)?);
+        crate::DecryptingReader::new(file, self, key).map_err(|e| e.with_path(path))
+    }
+
     #[doc(hidden)]
     pub fn buf_reader(&self, path: &Path) -> std::io::Result<impl std::io::BufRead> {
         let mut file = BufReader::new(File::open(path)?);
@@ -54,7 +352,11 @@ impl TocEntry {
         Ok(file.take(self.len))
     }
 
-    pub(crate) fn write_into(&self, mut writer: impl Write) -> crate::Result<()> {
+    pub(crate) fn write_into(
+        &self,
+        mut writer: impl Write,
+        version: FormatVersion,
+    ) -> crate::Result<()> {
         use byteorder::LE;
 
         writer.write_u64::<LE>(self.pos())?;
@@ -66,10 +368,40 @@ impl TocEntry {
         )?;
         writer.write_all(self.name())?;
 
+        if version >= FormatVersion::V2 {
+            self.write_flags_into(&mut writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the flags, followed by the flag-dependent fields.
+    pub(crate) fn write_flags_into(&self, mut writer: impl Write) -> crate::Result<()> {
+        writer.write_u8(self.flags)?;
+
+        if let Some(encryption) = &self.encryption {
+            encryption.write_into(&mut writer)?;
+        }
+
+        if let Some(checksum) = self.checksum {
+            writer.write_u128::<byteorder::LE>(checksum.into_u128())?;
+        }
+
+        if let Some(attributes) = &self.attributes {
+            attributes.write_into(&mut writer)?;
+        }
+
+        if let Some(volume) = self.volume {
+            writer.write_u32::<byteorder::LE>(volume)?;
+        }
+
         Ok(())
     }
 
-    pub(crate) fn read_from_file(reader: &mut impl Read) -> crate::Result<Self> {
+    pub(crate) fn read_from_file(
+        reader: &mut impl Read,
+        version: FormatVersion,
+    ) -> crate::Result<Self> {
         use byteorder::LE;
 
         let pos = reader.read_u64::<LE>()?;
@@ -79,6 +411,40 @@ impl TocEntry {
         let mut name = vec![0; section_name_len as usize];
         reader.read_exact(&mut name)?;
 
-        Ok(Self { name, pos, len })
+        let mut entry = Self {
+            name,
+            pos,
+            len,
+            ..Default::default()
+        };
+
+        if version >= FormatVersion::V2 {
+            entry.read_flags_from(reader)?;
+        }
+
+        Ok(entry)
+    }
+
+    /// Reads the flags, followed by the flag-dependent fields.
+    pub(crate) fn read_flags_from(&mut self, reader: &mut impl Read) -> crate::Result<()> {
+        self.flags = reader.read_u8()?;
+
+        if self.flags & FLAG_ENCRYPTED != 0 {
+            self.encryption = Some(EncryptionInfo::read_from_file(reader)?);
+        }
+
+        if self.flags & FLAG_CHECKSUM != 0 {
+            self.checksum = Some(Checksum::from_raw(reader.read_u128::<byteorder::LE>()?));
+        }
+
+        if self.flags & FLAG_ATTRIBUTES != 0 {
+            self.attributes = Some(SectionAttributes::read_from_file(reader)?);
+        }
+
+        if self.flags & FLAG_VOLUME != 0 {
+            self.volume = Some(reader.read_u32::<byteorder::LE>()?);
+        }
+
+        Ok(())
     }
 }
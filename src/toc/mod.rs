@@ -4,14 +4,71 @@
 
 use crate::TocEntry;
 
+pub mod compact;
+pub mod dir;
 pub mod entry;
 pub mod reader;
 pub mod writer;
 
+/// Encoding of the table of contents
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum TocEncoding {
+    /// Fixed-size integers and full names
+    #[default]
+    Plain,
+
+    /// Varints and front-coded names (see [`compact`])
+    Compact,
+}
+
 /// Table of contents
-pub struct Toc(pub(crate) Vec<TocEntry>);
+#[derive(Clone, Debug)]
+pub struct Toc {
+    pub(crate) entries: Vec<TocEntry>,
+    pub(crate) header: Option<TocEntry>,
+    pub(crate) parity: Option<TocEntry>,
+}
 
 impl Toc {
+    pub(crate) fn new(entries: Vec<TocEntry>) -> Self {
+        let (header, entries): (Vec<_>, Vec<_>) =
+            entries.into_iter().partition(TocEntry::is_header);
+
+        if header.len() > 1 {
+            log::warn!("Found {} header entries, using the first one", header.len());
+        }
+
+        let (parity, entries): (Vec<_>, Vec<_>) =
+            entries.into_iter().partition(TocEntry::is_parity);
+
+        if parity.len() > 1 {
+            log::warn!("Found {} parity entries, using the first one", parity.len());
+        }
+
+        Self {
+            entries,
+            header: header.into_iter().next(),
+            parity: parity.into_iter().next(),
+        }
+    }
+
+    /// Returns the header entry, if the archive has a header.
+    ///
+    /// The header is not part of the sections.
+    #[must_use]
+    pub fn header(&self) -> Option<&TocEntry> {
+        self.header.as_ref()
+    }
+
+    /// Returns the parity section entry, if the archive has parity
+    /// (see `Writer::use_parity`, requires the `parity` feature).
+    ///
+    /// The parity section is not part of the sections.
+    #[must_use]
+    pub fn parity(&self) -> Option<&TocEntry> {
+        self.parity.as_ref()
+    }
+
     /// Helper method to find a section by name.
     #[must_use]
     pub fn section(&self, name: &[u8]) -> Option<&TocEntry> {
@@ -23,6 +80,6 @@ impl std::ops::Deref for Toc {
     type Target = [TocEntry];
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.entries
     }
 }
@@ -2,11 +2,15 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use super::writer::TOC_MAGIC;
+use super::{
+    compact::{self, COMPACT_TOC_MAGIC},
+    writer::TOC_MAGIC,
+};
 use crate::{
     checksum::Checksum,
-    toc::{entry::TocEntry, Toc},
-    Result,
+    toc::{entry::TocEntry, Toc, TocEncoding},
+    trailer::reader::ParsedTrailer,
+    ErrorContext, Result,
 };
 use byteorder::ReadBytesExt;
 use std::io::{Read, Seek, SeekFrom};
@@ -43,16 +47,50 @@ impl<R: std::io::Read> std::io::Read for ChecksummedReader<R> {
 pub struct TocReader;
 
 impl TocReader {
-    pub fn from_reader<R: Read + Seek>(
+    pub fn from_reader<R: Read + Seek>(reader: &mut R, trailer: &ParsedTrailer) -> Result<Toc> {
+        log::trace!("Reading ToC");
+
+        if trailer.toc_pos > trailer.trailer_pos {
+            log::error!("Table of contents exceeds file");
+            return Err(crate::Error::TruncatedFile {
+                file_size: trailer.file_size,
+                context: ErrorContext::at(trailer.toc_pos),
+            });
+        }
+
+        let entries = match trailer.extension.toc_encoding {
+            TocEncoding::Plain => Self::read_plain(reader, trailer),
+            TocEncoding::Compact => Self::read_compact(reader, trailer),
+        }
+        .map_err(|e| e.truncated_at(trailer.file_size, trailer.toc_pos))?;
+
+        // NOTE: Sections of multi-volume archives are stored in other volumes
+        for entry in entries.iter().filter(|entry| entry.volume.is_none()) {
+            if entry
+                .pos
+                .checked_add(entry.len)
+                .is_none_or(|end| end > trailer.toc_pos)
+            {
+                log::error!("Section {:?} exceeds data region", entry.name);
+                return Err(crate::Error::SectionOutOfBounds {
+                    pos: entry.pos,
+                    len: entry.len,
+                    data_end: trailer.toc_pos,
+                    context: ErrorContext::section(&entry.name, entry.pos),
+                });
+            }
+        }
+
+        Ok(Toc::new(entries))
+    }
+
+    fn read_plain<R: Read + Seek>(
         reader: &mut R,
-        toc_pos: u64,
-        toc_checksum: Checksum,
-    ) -> Result<Toc> {
+        trailer: &ParsedTrailer,
+    ) -> Result<Vec<TocEntry>> {
         use byteorder::LE;
 
-        log::trace!("Reading ToC");
-
-        reader.seek(SeekFrom::Start(toc_pos))?;
+        reader.seek(SeekFrom::Start(trailer.toc_pos))?;
 
         let mut reader = ChecksummedReader::new(reader);
 
@@ -61,21 +99,134 @@ impl TocReader {
             reader.read_exact(&mut buf)?;
 
             if buf != TOC_MAGIC {
-                log::error!("Invalid version");
-                return Err(crate::Error::InvalidVersion);
+                log::error!("Invalid table of contents magic");
+                return Err(crate::Error::InvalidTocMagic {
+                    context: ErrorContext::at(trailer.toc_pos),
+                });
             }
         }
 
         let len = reader.read_u32::<LE>()?;
 
-        let mut entries = Vec::with_capacity(len as usize);
+        // NOTE: Don't trust the length for preallocation, the checksum is not verified yet
+        let mut entries = Vec::with_capacity((len as usize).min(1_000));
 
         for _ in 0..len {
-            entries.push(TocEntry::read_from_file(&mut reader)?);
+            entries.push(TocEntry::read_from_file(&mut reader, trailer.version)?);
         }
 
-        reader.checksum().check(toc_checksum)?;
+        // NOTE: The checksum also covers the trailer extension
+        reader.hasher.update(&trailer.raw_extension);
+
+        reader
+            .checksum()
+            .check(trailer.toc_checksum, ErrorContext::at(trailer.toc_pos))?;
+
+        Ok(entries)
+    }
+
+    /// Reads a compact table of contents.
+    ///
+    /// Unlike the plain encoding, the checksum is verified before decoding,
+    /// because the varint encoding is more prone to misinterpreting corrupted data.
+    fn read_compact<R: Read + Seek>(
+        reader: &mut R,
+        trailer: &ParsedTrailer,
+    ) -> Result<Vec<TocEntry>> {
+        use byteorder::LE;
+
+        let context = || ErrorContext::at(trailer.toc_pos);
+
+        if trailer
+            .toc_pos
+            .checked_add(trailer.toc_len)
+            .is_none_or(|end| end > trailer.trailer_pos)
+        {
+            log::error!("Table of contents exceeds file");
+            return Err(crate::Error::TruncatedFile {
+                file_size: trailer.file_size,
+                context: context(),
+            });
+        }
+
+        reader.seek(SeekFrom::Start(trailer.toc_pos))?;
+
+        #[allow(clippy::cast_possible_truncation)]
+        let mut bytes = vec![0; trailer.toc_len as usize];
+        reader.read_exact(&mut bytes)?;
+
+        let mut hasher = xxhash_rust::xxh3::Xxh3Default::new();
+        hasher.update(&bytes);
+        hasher.update(&trailer.raw_extension);
+
+        Checksum::from_raw(hasher.digest128()).check(trailer.toc_checksum, context())?;
+
+        let mut bytes = &bytes[..];
+
+        let Some(COMPACT_TOC_MAGIC) = bytes.get(..COMPACT_TOC_MAGIC.len()) else {
+            log::error!("Invalid table of contents magic");
+            return Err(crate::Error::InvalidTocMagic { context: context() });
+        };
+        bytes = bytes.get(COMPACT_TOC_MAGIC.len()..).unwrap_or_default();
+
+        let len = bytes.read_u32::<LE>()?;
+
+        compact::read_entries(&mut bytes, len).map_err(|e| {
+            log::error!("Invalid compact table of contents: {e}");
+            crate::Error::InvalidToc { context: context() }
+        })
+    }
+}
 
-        Ok(Toc(entries))
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::{
+        checksum_writer::ChecksummedWriter,
+        toc::{writer::TocWriter, TocEncoding},
+        trailer::extension::TrailerExtension,
+        version::FormatVersion,
+    };
+    use std::io::Cursor;
+    use test_log::test;
+
+    #[test]
+    fn toc_section_out_of_bounds() -> crate::Result<()> {
+        let entries = [TocEntry {
+            name: b"hello".to_vec(),
+            pos: 0,
+            len: 100,
+            ..Default::default()
+        }];
+
+        let mut bytes = vec![0; 50];
+        let mut writer = ChecksummedWriter::new(&mut bytes);
+        TocWriter::write_into(&mut writer, &entries, FormatVersion::V1, TocEncoding::Plain)?;
+        let toc_checksum = writer.checksum();
+        let toc_len = writer.len();
+
+        let trailer = ParsedTrailer {
+            version: FormatVersion::V1,
+            toc_checksum,
+            toc_pos: 50,
+            toc_len,
+            extension: TrailerExtension::default(),
+            raw_extension: vec![],
+            trailer_pos: 50 + toc_len,
+            file_size: 50 + toc_len,
+        };
+
+        assert!(matches!(
+            TocReader::from_reader(&mut Cursor::new(bytes), &trailer),
+            Err(crate::Error::SectionOutOfBounds {
+                pos: 0,
+                len: 100,
+                data_end: 50,
+                ..
+            })
+        ));
+
+        Ok(())
     }
 }
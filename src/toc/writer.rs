@@ -2,7 +2,11 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::{checksum::Checksum, checksum_writer::ChecksummedWriter, toc::entry::TocEntry};
+use super::{
+    compact::{self, COMPACT_TOC_MAGIC},
+    TocEncoding,
+};
+use crate::{toc::entry::TocEntry, version::FormatVersion};
 use byteorder::WriteBytesExt;
 use std::io::Write;
 
@@ -11,25 +15,38 @@ pub const TOC_MAGIC: &[u8] = b"TOC!";
 pub struct TocWriter;
 
 impl TocWriter {
-    pub fn write_into(mut writer: impl Write, entries: &[TocEntry]) -> crate::Result<Checksum> {
+    pub fn write_into(
+        mut writer: impl Write,
+        entries: &[TocEntry],
+        version: FormatVersion,
+        encoding: TocEncoding,
+    ) -> crate::Result<()> {
         use byteorder::LE;
 
         log::trace!("Writing ToC");
         log::trace!("ToC: {entries:#?}");
 
-        let mut writer = ChecksummedWriter::new(&mut writer);
-
-        writer.write_all(TOC_MAGIC)?;
+        writer.write_all(match encoding {
+            TocEncoding::Plain => TOC_MAGIC,
+            TocEncoding::Compact => COMPACT_TOC_MAGIC,
+        })?;
         writer.write_u32::<LE>(
             #[allow(clippy::expect_used)]
             u32::try_from(entries.len())
                 .expect("table of contents should not have 4 billion or more entries"),
         )?;
 
-        for entry in entries {
-            entry.write_into(&mut writer)?;
+        match encoding {
+            TocEncoding::Plain => {
+                for entry in entries {
+                    entry.write_into(&mut writer, version)?;
+                }
+            }
+            TocEncoding::Compact => {
+                compact::write_entries(&mut writer, entries)?;
+            }
         }
 
-        Ok(writer.checksum())
+        Ok(())
     }
 }
@@ -0,0 +1,188 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{toc::TocEncoding, AppId};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use std::io::{Read, Write};
+
+const TAG_APP_ID: u8 = 0x1;
+const TAG_TOC_ENCODING: u8 = 0x2;
+const TAG_BACKUP_TOC: u8 = 0x3;
+
+/// Size of the backup table of contents record (tag, length and position)
+///
+/// The record is always the last one, so it can be found without
+/// decoding the extension (see [`TrailerExtension::decode_backup_end`]).
+pub const BACKUP_TOC_RECORD_SIZE: usize = 1 + 2 + 8;
+
+const TOC_ENCODING_COMPACT: u8 = 0x1;
+
+/// Optional archive-level metadata, stored in front of the trailer (version 0x2 only)
+///
+/// Consists of a list of tagged records, so unknown records can be skipped.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct TrailerExtension {
+    /// Application ID and version
+    pub app_id: Option<(AppId, u32)>,
+
+    /// Encoding of the table of contents
+    pub toc_encoding: TocEncoding,
+
+    /// End of the backup copy of the table of contents and trailer, if any
+    pub backup_end: Option<u64>,
+}
+
+impl TrailerExtension {
+    fn write_record(mut writer: impl Write, tag: u8, value: &[u8]) -> crate::Result<()> {
+        writer.write_u8(tag)?;
+        writer.write_u16::<LE>(
+            #[allow(clippy::expect_used)]
+            u16::try_from(value.len()).expect("extension record should not be longer than 65535"),
+        )?;
+        writer.write_all(value)?;
+        Ok(())
+    }
+
+    /// Encodes the extension records, followed by their length.
+    pub fn encode(&self) -> crate::Result<Vec<u8>> {
+        let mut bytes = vec![];
+
+        if let Some((app_id, version)) = &self.app_id {
+            let mut value = app_id.as_bytes().to_vec();
+            value.write_u32::<LE>(*version)?;
+            Self::write_record(&mut bytes, TAG_APP_ID, &value)?;
+        }
+
+        if self.toc_encoding == TocEncoding::Compact {
+            Self::write_record(&mut bytes, TAG_TOC_ENCODING, &[TOC_ENCODING_COMPACT])?;
+        }
+
+        // NOTE: Must be the last record
+        if let Some(backup_end) = self.backup_end {
+            Self::write_record(&mut bytes, TAG_BACKUP_TOC, &backup_end.to_le_bytes())?;
+        }
+
+        let len = bytes.len();
+
+        bytes.write_u32::<LE>(
+            #[allow(clippy::expect_used)]
+            u32::try_from(len).expect("trailer extension should not be 4 GiB or larger"),
+        )?;
+
+        Ok(bytes)
+    }
+
+    /// Decodes the backup table of contents record, which is the last record of an extension.
+    ///
+    /// Returns `None` if the bytes are not a backup table of contents record.
+    pub fn decode_backup_end(bytes: &[u8; BACKUP_TOC_RECORD_SIZE]) -> Option<u64> {
+        let (header, value) = bytes.split_first_chunk::<3>()?;
+
+        if *header != [TAG_BACKUP_TOC, 8, 0] {
+            return None;
+        }
+
+        Some(u64::from_le_bytes(value.try_into().ok()?))
+    }
+
+    /// Decodes the extension records (without their length).
+    ///
+    /// Returns `None` if the records are malformed.
+    pub fn decode(mut bytes: &[u8]) -> Option<Self> {
+        let mut extension = Self::default();
+
+        while !bytes.is_empty() {
+            let tag = bytes.read_u8().ok()?;
+            let len = bytes.read_u16::<LE>().ok()?;
+
+            let mut value = vec![0; len.into()];
+            bytes.read_exact(&mut value).ok()?;
+
+            match tag {
+                TAG_APP_ID => {
+                    let (app_id, version) = value.split_last_chunk::<4>()?;
+                    let app_id = AppId::from_slice(app_id)?;
+                    extension.app_id = Some((app_id, u32::from_le_bytes(*version)));
+                }
+                TAG_TOC_ENCODING => {
+                    // NOTE: An unknown encoding cannot be skipped, because we could not read the ToC
+                    extension.toc_encoding = match *value {
+                        [TOC_ENCODING_COMPACT] => TocEncoding::Compact,
+                        _ => return None,
+                    };
+                }
+                TAG_BACKUP_TOC => {
+                    extension.backup_end = Some(u64::from_le_bytes(value.try_into().ok()?));
+                }
+                _ => {
+                    log::debug!("Skipping unknown trailer extension record {tag:#x}");
+                }
+            }
+        }
+
+        Some(extension)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn trailer_extension_roundtrip() -> crate::Result<()> {
+        let extension = TrailerExtension {
+            app_id: Some((AppId::from(b"SEGM"), 3)),
+            toc_encoding: TocEncoding::Compact,
+            backup_end: Some(1_234),
+        };
+
+        let bytes = extension.encode()?;
+        let (records, len) = bytes.split_last_chunk::<4>().unwrap();
+        assert_eq!(records.len() as u64, u64::from(u32::from_le_bytes(*len)));
+        assert_eq!(Some(extension), TrailerExtension::decode(records));
+
+        let backup_record = records.last_chunk::<BACKUP_TOC_RECORD_SIZE>().unwrap();
+        assert_eq!(
+            Some(1_234),
+            TrailerExtension::decode_backup_end(backup_record)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn trailer_extension_skip_unknown() -> crate::Result<()> {
+        let mut bytes = vec![];
+        TrailerExtension::write_record(&mut bytes, 0xFF, b"hello")?;
+        TrailerExtension::write_record(&mut bytes, TAG_APP_ID, b"SEGMENT!\x03\0\0\0")?;
+
+        assert_eq!(
+            Some(TrailerExtension {
+                app_id: Some((AppId::from(b"SEGMENT!"), 3)),
+                ..Default::default()
+            }),
+            TrailerExtension::decode(&bytes),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn trailer_extension_malformed() -> crate::Result<()> {
+        let mut bytes = vec![];
+        TrailerExtension::write_record(&mut bytes, TAG_APP_ID, b"SEGMENT\x03\0\0\0")?;
+        assert_eq!(None, TrailerExtension::decode(&bytes));
+
+        bytes.pop();
+        assert_eq!(None, TrailerExtension::decode(&bytes));
+
+        let mut bytes = vec![];
+        TrailerExtension::write_record(&mut bytes, TAG_TOC_ENCODING, &[0xFF])?;
+        assert_eq!(None, TrailerExtension::decode(&bytes));
+
+        Ok(())
+    }
+}
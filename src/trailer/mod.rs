@@ -2,5 +2,6 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
+pub mod extension;
 pub mod reader;
 pub mod writer;
@@ -2,63 +2,210 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use super::writer::TRAILER_MAGIC;
-use crate::{checksum::Checksum, Result};
+use super::{
+    extension::{TrailerExtension, BACKUP_TOC_RECORD_SIZE},
+    writer::{BACKUP_TRAILER_MAGIC, TRAILER_MAGIC, TRAILER_SIZE},
+};
+use crate::{checksum::Checksum, version::FormatVersion, ErrorContext, Result};
 use byteorder::ReadBytesExt;
 use std::io::{Read, Seek, SeekFrom};
 
-#[allow(clippy::cast_possible_wrap)]
-const TRAILER_SIZE: i64 = TRAILER_MAGIC.len() as i64 + 1 + 1 + 16 + 8 + 8;
-
 #[derive(Debug, Eq, PartialEq)]
 pub struct ParsedTrailer {
+    pub version: FormatVersion,
     pub toc_checksum: Checksum,
     pub toc_pos: u64,
+    pub toc_len: u64,
+
+    /// Trailer extension (version 0x2 only)
+    pub extension: TrailerExtension,
+
+    /// Raw bytes of the trailer extension, including its length,
+    /// which are covered by the table of contents checksum
+    pub raw_extension: Vec<u8>,
+
+    /// Start of the trailer (including its extension)
+    pub trailer_pos: u64,
+
+    /// File size in bytes
+    ///
+    /// For the backup copy, this is the end of the backup trailer.
+    pub file_size: u64,
 }
 
 pub struct TrailerReader;
 
 impl TrailerReader {
     pub fn from_reader<R: Read + Seek>(reader: &mut R) -> Result<ParsedTrailer> {
-        use byteorder::LE;
+        let file_size = reader.seek(SeekFrom::End(0))?;
+        Self::read_with_magic(reader, file_size, TRAILER_MAGIC)
+    }
+
+    /// Reads the backup trailer that ends at the given position
+    /// (see [`crate::Writer::use_redundant_toc`]).
+    pub fn backup_from_reader<R: Read + Seek>(
+        reader: &mut R,
+        backup_end: u64,
+    ) -> Result<ParsedTrailer> {
+        Self::read_with_magic(reader, backup_end, BACKUP_TRAILER_MAGIC)
+    }
 
-        log::trace!("Reading trailer");
+    fn read_with_magic<R: Read + Seek>(
+        reader: &mut R,
+        file_size: u64,
+        magic: &[u8],
+    ) -> Result<ParsedTrailer> {
+        log::trace!("Reading trailer ending at {file_size}");
 
-        reader.seek(SeekFrom::End(-TRAILER_SIZE))?;
+        let Some(trailer_pos) = file_size.checked_sub(TRAILER_SIZE.unsigned_abs()) else {
+            log::error!("File is too short ({file_size}B) to contain a trailer");
+            return Err(crate::Error::TruncatedFile {
+                file_size,
+                context: ErrorContext::at(0),
+            });
+        };
+
+        Self::read_at(reader, file_size, trailer_pos, magic)
+            .map_err(|e| e.truncated_at(file_size, trailer_pos))
+    }
+
+    fn read_at<R: Read + Seek>(
+        reader: &mut R,
+        file_size: u64,
+        mut trailer_pos: u64,
+        magic: &[u8],
+    ) -> Result<ParsedTrailer> {
+        use byteorder::LE;
+
+        reader.seek(SeekFrom::Start(trailer_pos))?;
 
         {
             let mut buf = [0u8; TRAILER_MAGIC.len()];
             reader.read_exact(&mut buf)?;
 
-            if buf != TRAILER_MAGIC {
-                log::error!("Invalid trailer header");
-                return Err(crate::Error::InvalidHeader);
+            if buf != magic {
+                log::error!("Invalid trailer magic");
+                return Err(crate::Error::InvalidTrailerMagic {
+                    context: ErrorContext::at(trailer_pos),
+                });
             }
         }
 
-        {
-            let version = reader.read_u8()?;
-            if version != 0x1 {
-                log::error!("Invalid version");
-                return Err(crate::Error::InvalidVersion);
-            }
-        }
+        let version = reader.read_u8()?;
+        let Ok(version) = FormatVersion::try_from(version) else {
+            log::error!("Invalid version");
+            return Err(crate::Error::VersionUnsupported {
+                found: version,
+                context: ErrorContext::at(trailer_pos + TRAILER_MAGIC.len() as u64),
+            });
+        };
 
         {
             let checksum_type = reader.read_u8()?;
             if checksum_type != 0x0 {
                 log::error!("Invalid checksum type");
-                return Err(crate::Error::UnsupportedChecksumType);
+                return Err(crate::Error::UnsupportedChecksumType {
+                    found: checksum_type,
+                    context: ErrorContext::at(trailer_pos + TRAILER_MAGIC.len() as u64 + 1),
+                });
             }
         }
 
         let toc_checksum = Checksum::from_raw(reader.read_u128::<LE>()?);
         let toc_pos = reader.read_u64::<LE>()?;
-        // let _toc_len = reader.read_u64::<LE>()?;
+        let toc_len = reader.read_u64::<LE>()?;
+
+        let (extension, raw_extension) = if version >= FormatVersion::V2 {
+            let (extension, raw_extension) = Self::read_extension(reader, trailer_pos)?;
+            trailer_pos -= raw_extension.len() as u64;
+            (extension, raw_extension)
+        } else {
+            (TrailerExtension::default(), vec![])
+        };
 
         Ok(ParsedTrailer {
+            version,
             toc_checksum,
             toc_pos,
+            toc_len,
+            extension,
+            raw_extension,
+            trailer_pos,
+            file_size,
         })
     }
+
+    /// Returns the possible ends of the backup copy of the table of contents and trailer
+    /// (see [`crate::Writer::use_redundant_toc`]), without validating the trailer.
+    ///
+    /// The backup ends where the table of contents starts, and its end is also stored in the
+    /// last trailer extension record, so one of them can be found even if the other is corrupted.
+    pub fn backup_ends<R: Read + Seek>(reader: &mut R) -> std::io::Result<Vec<u64>> {
+        use byteorder::LE;
+
+        let file_size = reader.seek(SeekFrom::End(0))?;
+
+        let Some(record_pos) =
+            file_size.checked_sub(TRAILER_SIZE.unsigned_abs() + 4 + BACKUP_TOC_RECORD_SIZE as u64)
+        else {
+            return Ok(vec![]);
+        };
+
+        reader.seek(SeekFrom::Start(record_pos))?;
+
+        let mut buf = [0; BACKUP_TOC_RECORD_SIZE];
+        reader.read_exact(&mut buf)?;
+
+        // NOTE: Table of contents position, followed by its length
+        reader.seek(SeekFrom::End(-16))?;
+        let toc_pos = reader.read_u64::<LE>()?;
+
+        let mut ends = TrailerExtension::decode_backup_end(&buf)
+            .into_iter()
+            .chain(std::iter::once(toc_pos))
+            .filter(|&end| end <= record_pos)
+            .collect::<Vec<_>>();
+        ends.dedup();
+
+        Ok(ends)
+    }
+
+    fn read_extension<R: Read + Seek>(
+        reader: &mut R,
+        trailer_pos: u64,
+    ) -> Result<(TrailerExtension, Vec<u8>)> {
+        use byteorder::LE;
+
+        log::trace!("Reading trailer extension");
+
+        let invalid = |offset| crate::Error::InvalidTrailerExtension {
+            context: ErrorContext::at(offset),
+        };
+
+        let Some(len_pos) = trailer_pos.checked_sub(4) else {
+            log::error!("Trailer extension exceeds file");
+            return Err(invalid(trailer_pos));
+        };
+
+        reader.seek(SeekFrom::Start(len_pos))?;
+        let len = reader.read_u32::<LE>()?;
+
+        let Some(extension_pos) = len_pos.checked_sub(len.into()) else {
+            log::error!("Trailer extension exceeds file");
+            return Err(invalid(len_pos));
+        };
+
+        reader.seek(SeekFrom::Start(extension_pos))?;
+
+        let mut raw = vec![0; len as usize + 4];
+        reader.read_exact(&mut raw)?;
+
+        let Some(extension) = TrailerExtension::decode(raw.get(..len as usize).unwrap_or_default())
+        else {
+            log::error!("Invalid trailer extension");
+            return Err(invalid(extension_pos));
+        };
+
+        Ok((extension, raw))
+    }
 }
@@ -2,26 +2,54 @@
 // This source code is licensed under both the Apache 2.0 and MIT License
 // (found in the LICENSE-* files in the repository)
 
-use crate::checksum::Checksum;
+use crate::{checksum::Checksum, version::FormatVersion};
 use byteorder::WriteBytesExt;
 
 pub const TRAILER_MAGIC: &[u8] = b"SFA!";
 
+/// Magic bytes of the backup trailer (see [`crate::Writer::use_redundant_toc`])
+///
+/// Differs from the regular trailer magic, so an archive stored in the last section
+/// cannot be mistaken for the backup.
+pub const BACKUP_TRAILER_MAGIC: &[u8] = b"SFA~";
+
+#[allow(clippy::cast_possible_wrap)]
+pub const TRAILER_SIZE: i64 = TRAILER_MAGIC.len() as i64 + 1 + 1 + 16 + 8 + 8;
+
 pub struct TrailerWriter;
 
 impl TrailerWriter {
     pub fn write_into<W: std::io::Write>(
+        writer: W,
+        toc_checksum: Checksum,
+        toc_pos: u64,
+        toc_len: u64,
+        version: FormatVersion,
+    ) -> crate::Result<()> {
+        Self::write_with_magic(
+            writer,
+            TRAILER_MAGIC,
+            toc_checksum,
+            toc_pos,
+            toc_len,
+            version,
+        )
+    }
+
+    pub fn write_with_magic<W: std::io::Write>(
         mut writer: W,
+        magic: &[u8],
         toc_checksum: Checksum,
         toc_pos: u64,
         toc_len: u64,
+        version: FormatVersion,
     ) -> crate::Result<()> {
         use byteorder::LE;
 
         log::trace!("Writing trailer");
 
-        writer.write_all(TRAILER_MAGIC)?;
-        writer.write_u8(0x1)?; // Version
+        writer.write_all(magic)?;
+        writer.write_u8(version.into_u8())?;
         writer.write_u8(0x0)?; // Checksum type, xxh3 = 0x0
         writer.write_u128::<LE>(toc_checksum.into_u128())?;
         writer.write_u64::<LE>(toc_pos)?;
@@ -0,0 +1,37 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+/// Disk format version
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum FormatVersion {
+    /// Initial format
+    V1,
+
+    /// Adds per-entry flags to the table of contents
+    ///
+    /// Only written if any feature requires it, so archives stay readable
+    /// by older versions of this crate whenever possible.
+    V2,
+}
+
+impl FormatVersion {
+    pub fn into_u8(self) -> u8 {
+        match self {
+            Self::V1 => 0x1,
+            Self::V2 => 0x2,
+        }
+    }
+}
+
+impl TryFrom<u8> for FormatVersion {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x1 => Ok(Self::V1),
+            0x2 => Ok(Self::V2),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,360 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{reader::Reader, AppId, ReaderOptions, Toc, TocEntry};
+use std::{
+    ffi::OsString,
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// Returns the path of the given volume (starting at 0) of a multi-volume archive,
+/// e.g. `name.sfa.001` for the first volume of `name.sfa`.
+pub fn volume_path(path: &Path, volume: u64) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(format!(".{:03}", volume + 1));
+    PathBuf::from(name)
+}
+
+/// Writes a continuous stream of bytes into volumes of a fixed size
+/// (see [`crate::Writer::new_multi_volume`])
+///
+/// Positions are relative to the start of the first volume, so volume `n`
+/// holds the bytes from `n * volume_size` up to (but not including) `(n + 1) * volume_size`.
+pub struct VolumeWriter {
+    path: PathBuf,
+    volume_size: u64,
+
+    /// The currently open volume, and its index
+    file: Option<(u64, File)>,
+
+    /// Whether the position of the open volume does not match the stream position
+    needs_seek: bool,
+
+    pos: u64,
+    volume_count: u64,
+}
+
+impl VolumeWriter {
+    /// Creates the writer, and removes the volumes of a previous archive with the same path.
+    pub(crate) fn create(path: &Path, volume_size: u64) -> std::io::Result<Self> {
+        if volume_size == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "volume size should not be 0",
+            ));
+        }
+
+        for volume in 0.. {
+            match std::fs::remove_file(volume_path(path, volume)) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            volume_size,
+            file: None,
+            needs_seek: false,
+            pos: 0,
+            volume_count: 0,
+        })
+    }
+
+    /// Returns the amount of volumes written so far.
+    #[must_use]
+    pub fn volume_count(&self) -> u64 {
+        self.volume_count
+    }
+
+    /// Returns the path of the given volume (starting at 0).
+    #[must_use]
+    pub fn volume_path(&self, volume: u64) -> PathBuf {
+        volume_path(&self.path, volume)
+    }
+
+    /// Syncs the open volume to disk.
+    ///
+    /// Other volumes are synced when they are closed, so this makes all written data durable.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn sync_all(&mut self) -> std::io::Result<()> {
+        match &mut self.file {
+            Some((_, file)) => file.sync_all(),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes all volumes written so far.
+    fn remove_volumes(&mut self) -> std::io::Result<()> {
+        self.file = None;
+
+        for volume in 0..self.volume_count {
+            match std::fs::remove_file(volume_path(&self.path, volume)) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        self.volume_count = 0;
+
+        Ok(())
+    }
+
+    /// Syncs the volumes once the archive is finished, or removes them if finishing failed,
+    /// so no incomplete archive is left behind.
+    pub(crate) fn finish(&mut self, finished: bool) -> std::io::Result<()> {
+        if finished {
+            let Err(e) = self.sync_all() else {
+                return Ok(());
+            };
+
+            if let Err(e) = self.remove_volumes() {
+                log::warn!("Failed to remove volumes of {}: {e}", self.path.display());
+            }
+
+            return Err(e);
+        }
+
+        self.remove_volumes()
+    }
+}
+
+impl Write for VolumeWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let volume = self.pos / self.volume_size;
+        let offset = self.pos % self.volume_size;
+
+        let file = match &mut self.file {
+            Some((open_volume, file)) if *open_volume == volume => file,
+            _ => {
+                // NOTE: The replaced volume is not written to anymore (unless seeking back),
+                // so make it durable before closing it
+                if let Some((_, file)) = self.file.take() {
+                    file.sync_all()?;
+                }
+
+                // NOTE: Earlier volumes are reopened when seeking back (e.g. to patch section frames)
+                let file = File::options()
+                    .write(true)
+                    .create(true)
+                    .truncate(false)
+                    .open(volume_path(&self.path, volume))?;
+
+                self.needs_seek = true;
+                self.volume_count = self.volume_count.max(volume + 1);
+                &mut self.file.insert((volume, file)).1
+            }
+        };
+
+        if self.needs_seek {
+            file.seek(SeekFrom::Start(offset))?;
+            self.needs_seek = false;
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let n = (self.volume_size - offset).min(buf.len() as u64) as usize;
+
+        #[allow(clippy::indexing_slicing)]
+        let n = file.write(&buf[..n])?;
+
+        self.pos += n as u64;
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.file {
+            Some((_, file)) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Seek for VolumeWriter {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+            SeekFrom::End(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    "cannot seek relative to the end of a multi-volume archive",
+                ));
+            }
+        };
+
+        let Some(pos) = pos else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            ));
+        };
+
+        if pos != self.pos {
+            self.pos = pos;
+            self.needs_seek = true;
+        }
+
+        Ok(pos)
+    }
+
+    fn stream_position(&mut self) -> std::io::Result<u64> {
+        Ok(self.pos)
+    }
+}
+
+/// Reader of an archive that is split into volumes (see [`crate::Writer::new_multi_volume`])
+///
+/// The table of contents is read from the final volume, and covers the sections of all volumes.
+pub struct MultiVolumeReader {
+    path: PathBuf,
+    volume_count: u32,
+    reader: Reader,
+}
+
+impl MultiVolumeReader {
+    /// Opens the volumes of the archive with the given path,
+    /// e.g. `name.sfa.001`, `name.sfa.002`, ... for `name.sfa`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the final volume is not a valid archive.
+    pub fn new(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::with_options(path, &ReaderOptions::default())
+    }
+
+    /// Opens the volumes of the archive with the given path, using the given options.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the final volume is not a valid archive
+    /// or does not match the options.
+    pub fn with_options(path: impl AsRef<Path>, options: &ReaderOptions) -> crate::Result<Self> {
+        let path = path.as_ref();
+
+        let mut volume_count: u32 = 0;
+        while volume_path(path, volume_count.into()).try_exists()? {
+            volume_count += 1;
+        }
+
+        // NOTE: Fails with the error of opening the first volume, if there is none
+        let final_path = volume_path(path, volume_count.saturating_sub(1).into());
+        let mut file = BufReader::with_capacity(4_096, File::open(&final_path)?);
+
+        let reader =
+            Reader::read_archive(&mut file, options).map_err(|e| e.with_path(&final_path))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            volume_count,
+            reader,
+        })
+    }
+
+    /// Returns the amount of volumes, including the final volume.
+    #[must_use]
+    pub fn volume_count(&self) -> u32 {
+        self.volume_count
+    }
+
+    /// Lists the table of contents.
+    #[must_use]
+    pub fn toc(&self) -> &Toc {
+        self.reader.toc()
+    }
+
+    /// Returns the archive header, if it has one.
+    ///
+    /// See [`crate::Writer::write_header`].
+    #[must_use]
+    pub fn header(&self) -> Option<&TocEntry> {
+        self.reader.header()
+    }
+
+    /// Returns the application ID, if the archive has one.
+    ///
+    /// See [`crate::Writer::use_app_id`].
+    #[must_use]
+    pub fn app_id(&self) -> Option<AppId> {
+        self.reader.app_id()
+    }
+
+    /// Returns the application-specific format version, if the archive has an application ID.
+    ///
+    /// See [`crate::Writer::use_app_id`].
+    #[must_use]
+    pub fn app_version(&self) -> Option<u32> {
+        self.reader.app_version()
+    }
+
+    /// Opens a reader of the given section, which continues into the following volumes
+    /// if the section does not end in the volume it starts in.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn reader(&self, entry: &TocEntry) -> std::io::Result<impl Read> {
+        let volume = entry.volume().unwrap_or_default();
+
+        let mut file = File::open(volume_path(&self.path, volume.into()))?;
+        file.seek(SeekFrom::Start(entry.pos()))?;
+
+        Ok(SpanningReader {
+            path: self.path.clone(),
+            final_volume: self.volume_count.saturating_sub(1),
+            volume,
+            file,
+            remaining: entry.len(),
+        })
+    }
+}
+
+/// Reads a section that may span multiple volumes
+struct SpanningReader {
+    path: PathBuf,
+    final_volume: u32,
+    volume: u32,
+    file: File,
+    remaining: u64,
+}
+
+impl Read for SpanningReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            if self.remaining == 0 || buf.is_empty() {
+                return Ok(0);
+            }
+
+            #[allow(clippy::cast_possible_truncation)]
+            let max = (buf.len() as u64).min(self.remaining) as usize;
+
+            #[allow(clippy::indexing_slicing)]
+            let n = self.file.read(&mut buf[..max])?;
+
+            if n > 0 {
+                self.remaining -= n as u64;
+                return Ok(n);
+            }
+
+            // NOTE: The final volume only holds the table of contents and trailer
+            if self.volume + 1 >= self.final_volume {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+            }
+
+            self.volume += 1;
+            self.file = File::open(volume_path(&self.path, self.volume.into()))?;
+        }
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright (c) 2025-present, fjall-rs
+// This source code is licensed under both the Apache 2.0 and MIT License
+// (found in the LICENSE-* files in the repository)
+
+use crate::{
+    toc::{Toc, TocEncoding},
+    AppId, Checksum, ContentDigest,
+};
+
+/// Summary of a finished archive (see [`crate::Writer::finish`])
+///
+/// Allows logging or indexing the archive without reopening it,
+/// and opening it without reading the table of contents again
+/// (see [`crate::Reader::from_summary`]).
+#[derive(Clone, Debug)]
+pub struct WriteSummary {
+    pub(crate) toc: Toc,
+    pub(crate) toc_checksum: Checksum,
+    pub(crate) toc_pos: u64,
+    pub(crate) toc_len: u64,
+    pub(crate) file_size: u64,
+    pub(crate) content_digest: ContentDigest,
+    pub(crate) app_id: Option<(AppId, u32)>,
+    pub(crate) toc_encoding: TocEncoding,
+    pub(crate) redundant_toc: bool,
+}
+
+impl WriteSummary {
+    /// Returns the table of contents, as it was written.
+    #[must_use]
+    pub fn toc(&self) -> &Toc {
+        &self.toc
+    }
+
+    /// Returns the checksum of the table of contents, as stored in the trailer.
+    #[must_use]
+    pub fn toc_checksum(&self) -> Checksum {
+        self.toc_checksum
+    }
+
+    /// Returns the position of the table of contents.
+    #[must_use]
+    pub fn toc_pos(&self) -> u64 {
+        self.toc_pos
+    }
+
+    /// Returns the length of the table of contents in bytes.
+    #[must_use]
+    pub fn toc_len(&self) -> u64 {
+        self.toc_len
+    }
+
+    /// Returns the total size of the archive in bytes.
+    #[must_use]
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Returns the number of bytes written per section (as stored,
+    /// so including encryption overhead), in table of contents order.
+    ///
+    /// The header is not included.
+    pub fn section_sizes(&self) -> impl Iterator<Item = (&[u8], u64)> + '_ {
+        self.toc.iter().map(|entry| (entry.name(), entry.len()))
+    }
+
+    /// Returns the content digest of the archive (see [`crate::Reader::content_digest`]).
+    #[must_use]
+    pub fn content_digest(&self) -> ContentDigest {
+        self.content_digest
+    }
+}
@@ -3,38 +3,674 @@
 // (found in the LICENSE-* files in the repository)
 
 use crate::{
+    buffered_writer::BufferedWriter,
+    checksum_writer::ChecksummedWriter,
+    detect::{write_leading_magic, LEADING_MAGIC_SIZE},
+    digest_writer::DigestingWriter,
+    frame::{write_frame_end, write_frame_len, write_frame_start},
     toc::{
-        entry::{SectionName, TocEntry},
+        dir::validate_path_name,
+        entry::{SectionAttributes, SectionName, TocEntry, FLAG_HEADER},
         writer::TocWriter,
+        Toc, TocEncoding,
     },
-    trailer::writer::TrailerWriter,
+    trailer::{
+        extension::TrailerExtension,
+        writer::{TrailerWriter, BACKUP_TRAILER_MAGIC},
+    },
+    version::FormatVersion,
+    volume::VolumeWriter,
+    AppId, Checksum, ContentDigest, Progress, WriteSummary,
+};
+use std::{
+    collections::HashMap,
+    io::{IoSlice, Read, Seek, SeekFrom, Write},
+    sync::Arc,
+};
+
+/// Maximum size of the buffer used to copy section data from a reader
+const COPY_BUFFER_SIZE: usize = 256 * 1_024;
+
+#[cfg(feature = "encryption")]
+use crate::encryption::{EncryptionKey, SectionEncryptor, DEFAULT_CHUNK_SIZE, MAX_CHUNK_SIZE};
+
+#[cfg(feature = "parity")]
+use crate::{
+    parity::{ParityConfig, ParityEncoder, DEFAULT_PARITY_BLOCK_SIZE, MAX_PARITY_BLOCK_SIZE},
+    toc::entry::FLAG_PARITY,
 };
-use std::io::{Seek, Write};
+
+#[cfg(feature = "signing")]
+use crate::signing::{write_signature_block, SectionDigest};
+
+#[cfg(feature = "signing")]
+use ed25519_dalek::SigningKey;
+
+/// Checks that a section name fits into the table of contents,
+/// which stores its length as 16-bit integer.
+fn check_name_len(name: &[u8]) -> std::io::Result<()> {
+    if name.len() > usize::from(u16::MAX) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "section name is too long ({} bytes, at most 65535 bytes)",
+                name.len()
+            ),
+        ));
+    }
+    Ok(())
+}
 
 /// Archive writer
-#[allow(clippy::struct_field_names)]
+#[allow(clippy::struct_field_names, clippy::struct_excessive_bools)]
 pub struct Writer<W: Write + Seek> {
-    writer: W,
+    writer: DigestingWriter<BufferedWriter<W>>,
     last_section_pos: u64,
-    section_name: SectionName,
+
+    /// Name of the section currently being written
+    ///
+    /// `None` while still in the implicit unnamed region at the start of the file.
+    section_name: Option<SectionName>,
+
     toc: Vec<TocEntry>,
+
+    /// Application ID of the leading magic block
+    leading_magic: Option<AppId>,
+
+    /// Whether anything was written yet
+    started: bool,
+
+    /// Error of an invalid combination of options, which the writer cannot recover from
+    invalid_options: Option<&'static str>,
+
+    extension: TrailerExtension,
+
+    /// Whether section names must be normalized relative paths
+    validate_path_names: bool,
+
+    /// File attributes of the section currently being written
+    section_attributes: Option<SectionAttributes>,
+
+    /// Whether the output must only depend on the written data
+    reproducible: bool,
+
+    /// Whether section checksums are stored in the table of contents
+    section_checksums: bool,
+
+    /// Checksums of the sections, in table of contents order
+    section_hashes: Vec<Checksum>,
+
+    /// Whether every section is preceded by a self-describing frame
+    section_frames: bool,
+
+    /// Position of the data length in the frame of the section currently being written
+    frame_len_pos: Option<u64>,
+
+    /// Whether a backup copy of the table of contents and trailer is written
+    redundant_toc: bool,
+
+    /// Size of the volumes, if the archive is split into volumes
+    volume_size: Option<u64>,
+
+    /// Start of the final volume, which positions in the trailer are relative to
+    final_volume_pos: u64,
+
+    /// Syncs or removes the volumes when finishing a multi-volume archive
+    /// (see [`Writer::new_multi_volume`])
+    finish_volumes: Option<fn(&mut W, bool) -> std::io::Result<()>>,
+
+    /// Whether sections with the same content as an earlier section are stored only once
+    deduplicate: bool,
+
+    /// Positions of the stored sections, by their checksum and length
+    stored_sections: HashMap<(u128, u64), u64>,
+
+    /// End of the data that was written, including discarded duplicate sections
+    data_end: u64,
+
+    /// Observer that is notified about written sections and bytes
+    progress: Option<Arc<dyn Progress>>,
+
+    /// Whether the start of the implicit unnamed section was reported to the observer
+    implicit_section_started: bool,
+
+    /// Duplicate handle of the underlying file, which files are copied into by the kernel
+    kernel_copy: Option<std::fs::File>,
+
+    /// Key and chunk size used to encrypt sections
+    #[cfg(feature = "encryption")]
+    encryption: Option<(EncryptionKey, u32)>,
+
+    /// Encryptor of the section currently being written
+    #[cfg(feature = "encryption")]
+    encryptor: Option<SectionEncryptor>,
+
+    /// Layout of the parity blocks, if parity is computed
+    #[cfg(feature = "parity")]
+    parity: Option<ParityConfig>,
+
+    /// Key used to sign the archive when finishing it
+    #[cfg(feature = "signing")]
+    signing_key: Option<SigningKey>,
+
+    /// SHA-256 digests of the sections, in table of contents order
+    #[cfg(feature = "signing")]
+    section_digests: Vec<SectionDigest>,
 }
 
 impl<W: Write + Seek> Writer<W> {
     /// Returns a mutable reference to the underlying writer.
+    ///
+    /// Data in the write buffer (see [`Writer::use_write_buffer`]) is not flushed,
+    /// so call [`Write::flush`] first to see everything that was written.
     pub fn get_mut(&mut self) -> &mut W {
-        &mut self.writer
+        self.writer.get_mut().get_mut()
     }
 
     /// Creates a new writer with the given I/O writer.
     #[must_use]
     pub fn from_writer(writer: W) -> Self {
         Self {
-            writer,
+            writer: DigestingWriter::new(BufferedWriter::new(writer)),
             last_section_pos: 0,
-            section_name: SectionName::new(),
+            section_name: None,
             toc: Vec::new(),
+            leading_magic: None,
+            started: false,
+            invalid_options: None,
+            extension: TrailerExtension::default(),
+            validate_path_names: false,
+            section_attributes: None,
+            reproducible: false,
+            section_checksums: false,
+            section_hashes: Vec::new(),
+            section_frames: false,
+            frame_len_pos: None,
+            redundant_toc: false,
+            volume_size: None,
+            final_volume_pos: 0,
+            finish_volumes: None,
+            deduplicate: false,
+            stored_sections: HashMap::new(),
+            data_end: 0,
+            progress: None,
+            implicit_section_started: false,
+            kernel_copy: None,
+
+            #[cfg(feature = "encryption")]
+            encryption: None,
+
+            #[cfg(feature = "encryption")]
+            encryptor: None,
+
+            #[cfg(feature = "parity")]
+            parity: None,
+
+            #[cfg(feature = "signing")]
+            signing_key: None,
+
+            #[cfg(feature = "signing")]
+            section_digests: Vec::new(),
+        }
+    }
+
+    /// Creates a writer for sections that were already written (see [`crate::recover`]),
+    /// so only the table of contents and trailer are appended when finishing.
+    ///
+    /// The given writer must be positioned at the end of the last section.
+    pub(crate) fn from_recovered(
+        writer: W,
+        sections: Vec<(TocEntry, Checksum)>,
+        end_pos: u64,
+    ) -> Self {
+        let (toc, section_hashes) = sections.into_iter().unzip();
+
+        Self {
+            last_section_pos: end_pos,
+            toc,
+            started: true,
+            section_hashes,
+            ..Self::from_writer(writer)
+        }
+    }
+
+    /// Writes a leading magic block at the start of the file.
+    ///
+    /// The block consists of the bytes `SFA`, a version byte, and the length and bytes
+    /// of the given user-defined application ID, and allows identifying an archive
+    /// without seeking to its end (see [`crate::detect`]).
+    ///
+    /// Usually, this is the same ID as the one stored in the trailer (see [`Writer::use_app_id`]),
+    /// which readers can check when opening the archive.
+    ///
+    /// The leading magic block is not part of any section.
+    ///
+    /// Has no effect if any data was already written.
+    #[must_use]
+    pub fn use_leading_magic(mut self, app_id: impl Into<AppId>) -> Self {
+        if !self.started {
+            self.leading_magic = Some(app_id.into());
+        }
+        self
+    }
+
+    /// Buffers writes in an internal buffer of the given capacity (in bytes),
+    /// so the underlying writer does not need to be buffered (e.g. using [`std::io::BufWriter`]).
+    ///
+    /// Writes that are at least as large as the buffer are passed through directly,
+    /// including vectored writes (see [`Write::write_vectored`]).
+    /// The buffer is flushed when the writer seeks (e.g. to patch section frames),
+    /// when calling [`Write::flush`] and when finishing the archive.
+    ///
+    /// The stream position is tracked by the writer either way,
+    /// so it is only queried from the underlying writer once.
+    ///
+    /// Defaults to 0, which disables buffering.
+    #[must_use]
+    pub fn use_write_buffer(mut self, capacity: usize) -> Self {
+        self.writer.get_mut().set_capacity(capacity);
+        self
+    }
+
+    /// Reports the written sections and bytes to the given observer.
+    ///
+    /// Bytes are reported when they are passed to the writer, so buffered or encrypted data
+    /// may not have reached the underlying writer yet. Sections are reported as finished
+    /// when the next section is started, or the archive is finished.
+    #[must_use]
+    pub fn use_progress(mut self, progress: Arc<dyn Progress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Stores an application-specific file type tag and format version in the trailer.
+    ///
+    /// Readers can check the tag using [`crate::ReaderOptions::expect_app_id`],
+    /// so opening the wrong kind of file fails early.
+    ///
+    /// To also identify the archive from the start of the file (e.g. when it was not
+    /// completely written), pass the same ID to [`Writer::use_leading_magic`].
+    ///
+    /// Note that this requires disk format version 0x2.
+    #[must_use]
+    pub fn use_app_id(mut self, app_id: impl Into<AppId>, version: u32) -> Self {
+        self.extension.app_id = Some((app_id.into(), version));
+        self
+    }
+
+    /// Requires section names to be normalized relative paths,
+    /// so they can be safely used as file paths when extracting the archive.
+    ///
+    /// Names must consist of `/`-separated, non-empty components, and must not
+    /// contain `.` or `..` components, backslashes or NUL bytes.
+    /// Starting a section with an invalid name fails with [`std::io::ErrorKind::InvalidInput`].
+    #[must_use]
+    pub fn use_path_validation(mut self) -> Self {
+        self.validate_path_names = true;
+        self
+    }
+
+    /// Stores a checksum of every section in the table of contents.
+    ///
+    /// The checksum covers the section data as stored in the archive,
+    /// and can be verified when extracting the archive (see [`crate::ExtractOptions::verify_checksums`]).
+    ///
+    /// Has no effect if any data was already written.
+    ///
+    /// Note that this requires disk format version 0x2.
+    #[must_use]
+    pub fn use_section_checksums(mut self) -> Self {
+        if !self.started {
+            self.section_checksums = true;
+        }
+        self
+    }
+
+    /// Precedes every section (including the header) with a self-describing frame,
+    /// so the sections can be recovered if writing the archive is interrupted
+    /// (see [`crate::recover`]).
+    ///
+    /// The frame contains the section name and length, and is followed by the
+    /// flag-dependent fields of the table of contents entry and a checksum of the section data.
+    /// The section length is filled in once the section is finished, so the writer seeks
+    /// back for every section.
+    ///
+    /// With frames, data can only be written after starting a section
+    /// (or writing the header), so there is no implicit unnamed section.
+    ///
+    /// Frames are skipped by readers, so the archive stays readable by older versions of this crate.
+    ///
+    /// Has no effect if any data was already written.
+    #[must_use]
+    pub fn use_section_frames(mut self) -> Self {
+        if !self.started {
+            self.section_frames = true;
+        }
+        self
+    }
+
+    /// Stores sections with the same content as an earlier section only once.
+    ///
+    /// Every section is hashed when it is finished. If an earlier section has the same
+    /// checksum (xxh3 128-bit) and length, the section data is discarded, and its table of contents
+    /// entry points to the data of the earlier section, so the sections share the same range.
+    ///
+    /// The header and encrypted sections are never deduplicated.
+    ///
+    /// Discarded data is overwritten by the following sections. Because the writer cannot truncate
+    /// the underlying writer, discarded data that is not overwritten (e.g. of a long section
+    /// followed by shorter ones) is zeroed when finishing the archive, and left as unused space
+    /// in front of the table of contents.
+    ///
+    /// Cannot be combined with section frames (see [`Writer::use_section_frames`]) or parity
+    /// (see `Writer::use_parity`), because they depend on the written data.
+    ///
+    /// Has no effect if any data was already written.
+    #[must_use]
+    pub fn use_deduplication(mut self) -> Self {
+        if !self.started {
+            self.deduplicate = true;
+        }
+        self
+    }
+
+    /// Makes the archive only depend on the written data, so identical inputs
+    /// always result in byte-identical archives (e.g. for content-addressed caching).
+    ///
+    /// Modification times are not recorded (see [`Writer::set_attributes`]),
+    /// and encryption is refused, because it requires random nonces.
+    ///
+    /// Everything else is deterministic regardless of this option: sections and
+    /// table of contents entries are stored in the order they are written
+    /// (see [`Writer::add_dir_recursive`] for sorted directory traversal),
+    /// there is no padding or compression, and signatures are deterministic.
+    ///
+    /// Has no effect if any data was already written.
+    #[must_use]
+    pub fn use_reproducible(mut self) -> Self {
+        if !self.started {
+            self.reproducible = true;
+        }
+        self
+    }
+
+    /// Returns `true` if the writer is in reproducible mode (see [`Writer::use_reproducible`]).
+    pub(crate) fn is_reproducible(&self) -> bool {
+        self.reproducible
+    }
+
+    /// Stores the table of contents in a compact encoding.
+    ///
+    /// Section names are front-coded, and positions and lengths are stored as varints,
+    /// which makes the table of contents a lot smaller for archives with many
+    /// sections with similar (e.g. hierarchical) names.
+    ///
+    /// Note that this requires disk format version 0x2.
+    #[must_use]
+    pub fn use_compact_toc(mut self) -> Self {
+        self.extension.toc_encoding = TocEncoding::Compact;
+        self
+    }
+
+    /// Writes a backup copy of the table of contents and trailer,
+    /// so the archive can still be read if either is corrupted.
+    ///
+    /// The backup is stored in front of the table of contents, and its position is stored
+    /// at a fixed position near the end of the file, so it can be found even if the trailer is corrupted.
+    /// [`crate::Reader`] falls back to the backup if the table of contents
+    /// or trailer cannot be read (see [`crate::Reader::toc_copy`]).
+    ///
+    /// Note that this requires disk format version 0x2.
+    #[must_use]
+    pub fn use_redundant_toc(mut self) -> Self {
+        self.redundant_toc = true;
+        self
+    }
+
+    /// Computes Reed-Solomon parity over all data written before the table of contents,
+    /// and stores it in a parity section, so damaged data can be repaired
+    /// (see [`crate::Reader::repair`]).
+    ///
+    /// The data is split into blocks of 4 KiB (see [`Writer::use_parity_block_size`]),
+    /// and every stripe of `data_shards` consecutive blocks is protected by `parity_shards`
+    /// parity blocks, so up to `parity_shards` damaged blocks per stripe can be repaired.
+    /// The shard counts must not be zero, and add up to at most 256.
+    ///
+    /// The parity section is not part of the sections (see [`crate::Toc::parity`]).
+    /// It is kept in memory until the archive is finished, so its size
+    /// (`parity_shards / data_shards` of the archive size) should be considered.
+    ///
+    /// Cannot be combined with section frames (see [`Writer::use_section_frames`]).
+    ///
+    /// Has no effect if any data was already written.
+    ///
+    /// Note that this requires disk format version 0x2.
+    #[cfg(feature = "parity")]
+    #[must_use]
+    pub fn use_parity(mut self, data_shards: u8, parity_shards: u8) -> Self {
+        if !self.started {
+            self.parity = Some(ParityConfig {
+                block_size: DEFAULT_PARITY_BLOCK_SIZE,
+                data_shards,
+                parity_shards,
+            });
+        }
+        self
+    }
+
+    /// Sets the size of the parity blocks (see [`Writer::use_parity`]).
+    ///
+    /// Smaller blocks allow repairing more scattered damage, but need more checksums.
+    ///
+    /// Defaults to 4 KiB. Writing fails if the block size is 0 or larger than 1 MiB.
+    #[cfg(feature = "parity")]
+    #[must_use]
+    pub fn use_parity_block_size(mut self, block_size: u32) -> Self {
+        if let Some(config) = &mut self.parity {
+            if !self.started {
+                config.block_size = block_size;
+            }
+        }
+        self
+    }
+
+    /// Encrypts all sections written after this call, using XChaCha20-Poly1305.
+    ///
+    /// Sections are split into chunks of 64 KiB, which are encrypted and
+    /// authenticated individually (see [`Writer::use_encryption_chunk_size`]).
+    /// The archive header, section names and table of contents are not encrypted.
+    ///
+    /// Encrypted sections can be read using [`crate::DecryptingReader`].
+    ///
+    /// Note that this requires disk format version 0x2.
+    #[cfg(feature = "encryption")]
+    #[must_use]
+    pub fn use_encryption(mut self, key: EncryptionKey) -> Self {
+        let chunk_size = self
+            .encryption
+            .as_ref()
+            .map_or(DEFAULT_CHUNK_SIZE, |(_, chunk_size)| *chunk_size);
+
+        self.encryption = Some((key, chunk_size));
+        self
+    }
+
+    /// Sets the amount of plaintext bytes per encrypted chunk.
+    ///
+    /// Smaller chunks make random access cheaper, but add 16 bytes of overhead each.
+    ///
+    /// Has no effect if encryption is not enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the chunk size is 0 or larger than 16 MiB.
+    #[cfg(feature = "encryption")]
+    #[must_use]
+    pub fn use_encryption_chunk_size(mut self, chunk_size: u32) -> Self {
+        assert!(chunk_size > 0, "chunk size should not be 0");
+        assert!(
+            chunk_size <= MAX_CHUNK_SIZE,
+            "chunk size should not be larger than 16 MiB"
+        );
+
+        if let Some((_, size)) = &mut self.encryption {
+            *size = chunk_size;
+        }
+        self
+    }
+
+    /// Checks that the options can be used together.
+    fn check_options(&self) -> Result<(), &'static str> {
+        #[cfg(feature = "parity")]
+        if let Some(config) = self.parity {
+            // NOTE: A stripe of blocks is kept in memory
+            if config.block_size == 0 || config.block_size > MAX_PARITY_BLOCK_SIZE {
+                return Err("parity block size must be between 1 byte and 1 MiB");
+            }
+
+            // NOTE: Frames are patched after being written, which would invalidate the parity
+            if self.section_frames {
+                return Err("parity cannot be used with section frames");
+            }
+
+            if self.volume_size.is_some() {
+                return Err("parity cannot be used with multi-volume archives");
+            }
+
+            if self.deduplicate {
+                return Err("parity cannot be used with deduplication");
+            }
+        }
+
+        // NOTE: Frames of discarded sections would still be found when recovering the archive
+        if self.deduplicate && self.section_frames {
+            return Err("deduplication cannot be used with section frames");
+        }
+
+        #[cfg(feature = "encryption")]
+        if self.reproducible && self.encryption.is_some() {
+            return Err("encryption cannot be used in reproducible mode");
+        }
+
+        Ok(())
+    }
+
+    fn ensure_started(&mut self) -> std::io::Result<()> {
+        // NOTE: Options are checked before anything is written, and the error sticks,
+        // so retrying cannot silently ignore any of them. They are checked again before
+        // every write, because some options can still be changed (see `Writer::use_encryption`)
+        if self.invalid_options.is_none() {
+            self.invalid_options = self.check_options().err();
+        }
+
+        if let Some(error) = self.invalid_options {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, error));
+        }
+
+        if !self.started {
+            #[cfg(feature = "parity")]
+            let parity = self.parity.map(ParityEncoder::new).transpose()?;
+
+            self.started = true;
+
+            #[cfg(feature = "parity")]
+            if let Some(encoder) = parity {
+                self.writer.set_parity(encoder);
+            }
+
+            if let Some(app_id) = self.leading_magic {
+                write_leading_magic(&mut self.writer, app_id)?;
+                self.writer.reset();
+                self.last_section_pos += LEADING_MAGIC_SIZE as u64;
+            }
+
+            // NOTE: Data written before the first named section is encrypted as well
+            self.start_encryptor(&[]);
+        }
+        Ok(())
+    }
+
+    /// Writes the frame of a section, if frames are enabled.
+    fn start_frame(&mut self, name: &[u8]) -> std::io::Result<()> {
+        if self.section_frames {
+            let frame_pos = self.writer.stream_position()?;
+            let len_offset = write_frame_start(&mut self.writer, name)?;
+            self.writer.reset();
+
+            self.frame_len_pos = Some(frame_pos + len_offset);
+            self.last_section_pos = self.writer.stream_position()?;
+        }
+        Ok(())
+    }
+
+    /// Starts encrypting the section of the given name, if encryption is enabled.
+    #[allow(clippy::unused_self, clippy::needless_pass_by_ref_mut)]
+    fn start_encryptor(&mut self, name: &[u8]) {
+        #[cfg(feature = "encryption")]
+        {
+            self.encryptor = self
+                .encryption
+                .as_ref()
+                .map(|(key, chunk_size)| SectionEncryptor::new(key, *chunk_size, name));
         }
+
+        #[cfg(not(feature = "encryption"))]
+        let _ = name;
+    }
+}
+
+impl Writer<std::fs::File> {
+    /// Copies files into the archive using the kernel (e.g. `copy_file_range` on Linux),
+    /// see [`Writer::write_section_from_file`].
+    ///
+    /// The data does not need to be written from user space, but it is still read once
+    /// to hash it (see [`crate::ContentDigest`]), so the file must not be modified
+    /// while it is copied. Encrypted sections are always copied using a buffer.
+    ///
+    /// Has no effect if the file handle cannot be duplicated.
+    #[must_use]
+    pub fn use_kernel_copy(mut self) -> Self {
+        self.kernel_copy = self.get_mut().try_clone().ok();
+        self
+    }
+}
+
+impl Writer<VolumeWriter> {
+    /// Creates a writer that splits the archive into volumes of the given size,
+    /// e.g. `name.sfa.001`, `name.sfa.002`, ... for `name.sfa`.
+    ///
+    /// Sections are split at volume boundaries, so every volume (except the last two)
+    /// has exactly the given size. The table of contents and trailer are stored in a
+    /// final volume of their own, and their entries reference the volume each section
+    /// starts in (see [`TocEntry::volume`]). Finishing the archive fails if they do not
+    /// fit into a volume.
+    ///
+    /// Existing volumes of an archive with the same path are removed.
+    ///
+    /// Every volume is synced to disk once it is complete, and if finishing the archive
+    /// fails, the written volumes are removed.
+    ///
+    /// The archive can be read using [`crate::MultiVolumeReader`].
+    /// Positions in the [`WriteSummary`] are relative to the final volume.
+    ///
+    /// Cannot be combined with parity (see `Writer::use_parity`).
+    ///
+    /// Note that this requires disk format version 0x2.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the volume size is 0.
+    pub fn new_multi_volume(
+        path: impl AsRef<std::path::Path>,
+        volume_size: u64,
+    ) -> std::io::Result<Self> {
+        let writer = VolumeWriter::create(path.as_ref(), volume_size)?;
+
+        Ok(Self {
+            volume_size: Some(volume_size),
+            finish_volumes: Some(VolumeWriter::finish),
+            ..Self::from_writer(writer)
+        })
     }
 }
 
@@ -44,82 +680,767 @@ impl<W: Write + Seek> std::io::Write for Writer<W> {
     }
 
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.writer.write(buf)
+        self.prepare_write(buf.len())?;
+
+        #[cfg(feature = "encryption")]
+        if let Some(encryptor) = &mut self.encryptor {
+            encryptor.write(&mut self.writer, buf)?;
+            self.report_bytes(buf.len());
+            return Ok(buf.len());
+        }
+
+        let n = self.writer.write(buf)?;
+        self.report_bytes(n);
+        Ok(n)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+        self.prepare_write(bufs.iter().map(|buf| buf.len()).sum())?;
+
+        #[cfg(feature = "encryption")]
+        if let Some(encryptor) = &mut self.encryptor {
+            for buf in bufs {
+                encryptor.write(&mut self.writer, buf)?;
+            }
+
+            let n = bufs.iter().map(|buf| buf.len()).sum();
+            self.report_bytes(n);
+            return Ok(n);
+        }
+
+        let n = self.writer.write_vectored(bufs)?;
+        self.report_bytes(n);
+        Ok(n)
+    }
+}
+
+impl<W: Write + Seek> Writer<W> {
+    /// Checks that data can be written, before writing `len` bytes.
+    fn prepare_write(&mut self, len: usize) -> std::io::Result<()> {
+        self.ensure_started()?;
+
+        if self.section_frames && self.section_name.is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "data can only be written after starting a section when using section frames",
+            ));
+        }
+
+        // NOTE: Data written before the first named section becomes an implicit unnamed section,
+        // which starts with its first byte
+        if len > 0 && self.section_name.is_none() && !self.implicit_section_started {
+            self.implicit_section_started = true;
+            self.report_section_start(&[]);
+        }
+
+        Ok(())
+    }
+
+    fn report_bytes(&self, n: usize) {
+        if let Some(progress) = &self.progress {
+            progress.on_bytes(n as u64);
+        }
+    }
+
+    fn report_section_start(&self, name: &[u8]) {
+        if let Some(progress) = &self.progress {
+            progress.on_section_start(name);
+        }
     }
 }
 
 impl<W: Write + Seek> Writer<W> {
+    /// Writes the archive header.
+    ///
+    /// The header is stored at the start of the file, before the first section,
+    /// so it can be used for a fixed-format preamble (e.g. magic bytes and versions).
+    ///
+    /// It is not part of the sections, and can be retrieved using [`crate::Reader::header`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or any data was already written.
+    pub fn write_header(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.ensure_started()?;
+
+        if self.writer.stream_position()? != self.last_section_pos
+            || self.section_name.is_some()
+            || !self.toc.is_empty()
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "header must be written before any other data",
+            ));
+        }
+
+        self.start_frame(&[])?;
+        self.report_section_start(&[]);
+
+        let file_pos = self.writer.stream_position()?;
+        self.writer.write_all(bytes)?;
+        self.report_bytes(bytes.len());
+
+        self.last_section_pos = file_pos + bytes.len() as u64;
+
+        self.push_entry(TocEntry {
+            name: SectionName::new(),
+            pos: file_pos,
+            len: bytes.len() as u64,
+            flags: FLAG_HEADER,
+            ..Default::default()
+        })?;
+
+        Ok(())
+    }
+
     /// Starts the first named section.
     ///
     /// # Errors
     ///
-    /// Returns error, if an IO error occurred.
+    /// Returns error, if an IO error occurred, or the name is invalid
+    /// (longer than 65535 bytes, see [`Writer::use_path_validation`]).
     pub fn start(&mut self, name: impl Into<SectionName>) -> std::io::Result<()> {
+        let name = name.into();
+
+        check_name_len(&name)?;
+
+        if self.validate_path_names {
+            if let Err(e) = validate_path_name(&name) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "invalid section name {:?}: {e}",
+                        String::from_utf8_lossy(&name)
+                    ),
+                ));
+            }
+        }
+
+        self.append_toc_entry()?;
+        self.start_frame(&name)?;
+        self.report_section_start(&name);
+        self.start_encryptor(&name);
+        self.section_name = Some(name);
+        self.section_attributes = None;
+        Ok(())
+    }
+
+    /// Starts a new section, and fills it with all data of the given reader.
+    ///
+    /// Returns the section length.
+    ///
+    /// The data is copied using a large buffer, so the reader does not need to be buffered.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the name is invalid
+    /// (longer than 65535 bytes, see [`Writer::use_path_validation`]).
+    pub fn write_section_from(
+        &mut self,
+        name: impl Into<SectionName>,
+        reader: &mut impl Read,
+    ) -> std::io::Result<u64> {
+        self.start(name)?;
+        self.copy_from(reader, None)
+    }
+
+    /// Starts a new section, and fills it with the data of the given file,
+    /// from its current position to its end.
+    ///
+    /// Like [`Writer::write_section_from`], but the file size is used to size the copy buffer,
+    /// so small files do not allocate a large buffer. If the archive is written to a file,
+    /// the data can be copied by the kernel instead (see [`Writer::use_kernel_copy`]).
+    ///
+    /// Returns the section length, which may differ from the file size
+    /// if the file is modified while it is copied.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred, or the name is invalid
+    /// (longer than 65535 bytes, see [`Writer::use_path_validation`]).
+    pub fn write_section_from_file(
+        &mut self,
+        name: impl Into<SectionName>,
+        file: &std::fs::File,
+    ) -> std::io::Result<u64> {
+        let mut file = file;
+        let size_hint = file
+            .metadata()?
+            .len()
+            .saturating_sub(file.stream_position()?);
+
+        self.start(name)?;
+
+        if let Some(copied) = self.kernel_copy_from(file, size_hint)? {
+            return Ok(copied);
+        }
+
+        self.copy_from(&mut file, Some(size_hint))
+    }
+
+    /// Copies the rest of the file into the current section using the kernel, if enabled
+    /// (see [`Writer::use_kernel_copy`]), and hashes the copied data in a separate read pass.
+    ///
+    /// Returns the amount of copied bytes, or `None` if the data must be copied using a buffer.
+    fn kernel_copy_from(
+        &mut self,
+        mut file: &std::fs::File,
+        size_hint: u64,
+    ) -> std::io::Result<Option<u64>> {
+        let Some(sink) = &self.kernel_copy else {
+            return Ok(None);
+        };
+
+        #[cfg(feature = "encryption")]
+        if self.encryptor.is_some() {
+            return Ok(None);
+        }
+
+        let mut sink = sink;
+        let start = file.stream_position()?;
+
+        self.writer.flush()?;
+        let copied = std::io::copy(&mut file, &mut sink)?;
+
+        // NOTE: The duplicate handle shares the file offset, so the position is queried again
+        self.writer.get_mut().invalidate_position();
+
+        if let Some(progress) = &self.progress {
+            progress.on_bytes(copied);
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let buf_size = size_hint.min(COPY_BUFFER_SIZE as u64) as usize;
+
+        file.seek(SeekFrom::Start(start))?;
+
+        let mut buf = vec![0; buf_size.max(1)];
+        let mut reader = file.take(copied);
+
+        let mut hashed = 0;
+
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            #[allow(clippy::indexing_slicing)]
+            self.writer.update(&buf[..n]);
+
+            hashed += n as u64;
+        }
+
+        if hashed != copied {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+        }
+
+        Ok(Some(copied))
+    }
+
+    /// Writes all data of the reader into the current section.
+    ///
+    /// Returns the amount of copied bytes.
+    fn copy_from(
+        &mut self,
+        reader: &mut impl Read,
+        size_hint: Option<u64>,
+    ) -> std::io::Result<u64> {
+        // NOTE: Reading one more byte than the hint detects the end of the file in one read
+        let buf_size = size_hint
+            .and_then(|len| usize::try_from(len.saturating_add(1)).ok())
+            .map_or(COPY_BUFFER_SIZE, |len| len.min(COPY_BUFFER_SIZE));
+
+        let mut buf = vec![0; buf_size];
+        let mut copied = 0;
+
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            #[allow(clippy::indexing_slicing)]
+            self.write_all(&buf[..n])?;
+
+            copied += n as u64;
+        }
+
+        Ok(copied)
+    }
+
+    /// Sets the file attributes of the current section.
+    ///
+    /// The attributes are restored when extracting the archive (see [`crate::Reader::extract_to`]).
+    ///
+    /// In reproducible mode, the modification time is dropped (see [`Writer::use_reproducible`]).
+    ///
+    /// Note that this requires disk format version 0x2.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if no named section was started yet.
+    pub fn set_attributes(&mut self, attributes: SectionAttributes) -> std::io::Result<()> {
+        if self.section_name.is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "attributes can only be set after starting a section",
+            ));
+        }
+
+        let attributes = if self.reproducible {
+            attributes.without_mtime()
+        } else {
+            attributes
+        };
+
+        self.section_attributes =
+            (attributes != SectionAttributes::default()).then_some(attributes);
+
+        Ok(())
+    }
+
+    /// Copies a section as-is (without encrypting it again),
+    /// keeping the optional fields of the source entry.
+    pub(crate) fn copy_raw_section(
+        &mut self,
+        name: SectionName,
+        reader: impl Read,
+        source: &TocEntry,
+    ) -> std::io::Result<()> {
+        check_name_len(&name)?;
+
         self.append_toc_entry()?;
-        self.section_name = name.into();
+        self.start_frame(&name)?;
+        self.report_section_start(&name);
+
+        let pos = self.writer.stream_position()?;
+        let copied = std::io::copy(&mut reader.take(source.len), &mut self.writer)?;
+
+        if let Some(progress) = &self.progress {
+            progress.on_bytes(copied);
+        }
+
+        if copied != source.len {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+        }
+
+        self.last_section_pos = pos + source.len;
+
+        self.push_entry(TocEntry {
+            name,
+            pos,
+            len: source.len,
+            flags: 0,
+            encryption: source.encryption.clone(),
+            checksum: source.checksum,
+            attributes: source.attributes,
+            volume: None,
+        })
+    }
+
+    /// Adds an entry for the data written since the last entry.
+    ///
+    /// If the section is framed, the frame is finished as well,
+    /// so the next section starts after it.
+    fn push_entry(&mut self, mut entry: TocEntry) -> std::io::Result<()> {
+        #[cfg(feature = "signing")]
+        self.section_digests.push(self.writer.take_digest());
+
+        let checksum = self.writer.take_checksum();
+        self.section_hashes.push(checksum);
+
+        // NOTE: Raw copies keep the checksum of their source, if any
+        if self.section_checksums {
+            entry.checksum = Some(checksum);
+        }
+
+        entry.update_flags();
+
+        if self.deduplicate {
+            self.deduplicate_entry(&mut entry, checksum)?;
+        }
+
+        if let Some(len_pos) = self.frame_len_pos.take() {
+            write_frame_end(&mut self.writer, &entry, checksum)?;
+            self.writer.reset();
+
+            let end_pos = self.writer.stream_position()?;
+            self.writer.seek(SeekFrom::Start(len_pos))?;
+            write_frame_len(self.writer.get_mut(), &entry.name, entry.len)?;
+            self.writer.seek(SeekFrom::Start(end_pos))?;
+
+            self.last_section_pos = end_pos;
+        }
+
+        if let Some(progress) = &self.progress {
+            progress.on_section_end(&entry);
+        }
+
+        self.toc.push(entry);
+
+        Ok(())
+    }
+
+    /// Points the entry to the data of an earlier section with the same content, if any,
+    /// and discards its own data.
+    fn deduplicate_entry(
+        &mut self,
+        entry: &mut TocEntry,
+        checksum: Checksum,
+    ) -> std::io::Result<()> {
+        if entry.is_header() || entry.encryption.is_some() || entry.len == 0 {
+            return Ok(());
+        }
+
+        match self.stored_sections.get(&(checksum.into_u128(), entry.len)) {
+            Some(&pos) => {
+                log::trace!(
+                    "Section {:?} is a duplicate of the section at {pos}",
+                    String::from_utf8_lossy(&entry.name),
+                );
+
+                self.data_end = self.data_end.max(self.writer.stream_position()?);
+                self.writer.seek(SeekFrom::Start(entry.pos))?;
+                self.last_section_pos = entry.pos;
+
+                entry.pos = pos;
+            }
+            None => {
+                self.stored_sections
+                    .insert((checksum.into_u128(), entry.len), entry.pos);
+            }
+        }
+
         Ok(())
     }
 
     fn append_toc_entry(&mut self) -> std::io::Result<()> {
+        self.ensure_started()?;
+
+        #[allow(unused_mut)]
+        let mut encryption = None;
+
+        #[cfg(feature = "encryption")]
+        if let Some(encryptor) = self.encryptor.take() {
+            if self.section_name.is_some() || encryptor.plaintext_len() > 0 {
+                encryption = Some(encryptor.finish(&mut self.writer)?);
+            }
+        }
+
         let file_pos = self.writer.stream_position()?;
 
-        if file_pos > 0 {
-            let name = std::mem::take(&mut self.section_name);
-            self.toc.push(TocEntry {
+        // NOTE: Data written before the first named section becomes an implicit
+        // unnamed section, but only if there actually is some data
+        let name = match self.section_name.take() {
+            Some(name) => Some(name),
+            None if file_pos > self.last_section_pos => Some(SectionName::new()),
+            None => None,
+        };
+
+        let section_pos = std::mem::replace(&mut self.last_section_pos, file_pos);
+
+        if let Some(name) = name {
+            let attributes = self.section_attributes.take();
+
+            self.push_entry(TocEntry {
                 name,
-                pos: self.last_section_pos,
-                len: file_pos - self.last_section_pos,
-            });
+                pos: section_pos,
+                len: file_pos - section_pos,
+                flags: 0,
+                encryption,
+                checksum: None,
+                attributes,
+                volume: None,
+            })?;
         }
 
-        self.last_section_pos = file_pos;
-
         Ok(())
     }
 
-    fn append_trailer(mut writer: &mut W, toc: &[TocEntry]) -> crate::Result<()> {
-        // Write ToC
-        let toc_pos = writer.stream_position()?;
-        let toc_checksum = TocWriter::write_into(&mut writer, toc)?;
+    /// Writes the table of contents and trailer.
+    ///
+    /// Returns the position, length and checksum of the table of contents.
+    fn append_trailer(&mut self) -> crate::Result<(u64, u64, Checksum)> {
+        // NOTE: Only use the newer format if we actually need it,
+        // so older readers can still read the archive
+        let version = if self.toc.iter().any(|entry| entry.flags != 0)
+            || self.extension != TrailerExtension::default()
+            || self.redundant_toc
+        {
+            FormatVersion::V2
+        } else {
+            FormatVersion::V1
+        };
+
+        if self.redundant_toc {
+            // NOTE: The backup is a complete copy of the ToC and trailer
+            // (with a different trailer magic), as if the archive ended after it
+            let extension = self.extension.encode()?;
+            let (toc_pos, toc_len, toc_checksum) = self.write_toc(version, &extension)?;
 
-        let after_toc_pos = writer.stream_position()?;
-        let toc_len = after_toc_pos - toc_pos;
+            self.writer.write_all(&extension)?;
+            TrailerWriter::write_with_magic(
+                &mut self.writer,
+                BACKUP_TRAILER_MAGIC,
+                toc_checksum,
+                toc_pos,
+                toc_len,
+                version,
+            )?;
+            self.writer.reset();
+
+            self.extension.backup_end = Some(self.volume_position()?);
+        }
+
+        let extension = if version >= FormatVersion::V2 {
+            self.extension.encode()?
+        } else {
+            Vec::new()
+        };
+
+        let (toc_pos, toc_len, toc_checksum) = self.write_toc(version, &extension)?;
+
+        let mut trailer = Vec::new();
+        TrailerWriter::write_into(&mut trailer, toc_checksum, toc_pos, toc_len, version)?;
+
+        #[cfg(feature = "signing")]
+        if let Some(key) = &self.signing_key {
+            let toc_digest = self.writer.take_digest();
+
+            write_signature_block(
+                &mut self.writer,
+                key,
+                &toc_digest,
+                &extension,
+                &trailer,
+                &self.section_digests,
+            )?;
+        }
+
+        self.writer.write_all(&extension)?;
 
         // Write trailer
-        TrailerWriter::write_into(writer, toc_checksum, toc_pos, toc_len)
+        self.writer.write_all(&trailer)?;
+
+        Ok((toc_pos, toc_len, toc_checksum))
     }
 
-    /// Finishes the file.
-    ///
-    /// # Errors
+    /// Writes the table of contents.
     ///
-    /// Returns error, if an IO error occurred.
-    #[allow(clippy::missing_panics_doc)]
-    pub fn finish(mut self) -> crate::Result<()> {
+    /// Returns the position, length and checksum of the table of contents,
+    /// which also covers the given trailer extension.
+    fn write_toc(
+        &mut self,
+        version: FormatVersion,
+        extension: &[u8],
+    ) -> crate::Result<(u64, u64, Checksum)> {
+        let toc_pos = self.volume_position()?;
+
+        let mut checksummed_writer = ChecksummedWriter::new(&mut self.writer);
+
+        TocWriter::write_into(
+            &mut checksummed_writer,
+            &self.toc,
+            version,
+            self.extension.toc_encoding,
+        )?;
+        let toc_len = checksummed_writer.len();
+
+        // NOTE: The ToC checksum also covers the trailer extension
+        checksummed_writer.update(extension);
+
+        Ok((toc_pos, toc_len, checksummed_writer.checksum()))
+    }
+
+    /// Writes the parity section, if parity is computed.
+    #[cfg(feature = "parity")]
+    fn append_parity(&mut self) -> std::io::Result<()> {
+        let Some(encoder) = self.writer.take_parity() else {
+            return Ok(());
+        };
+
+        let pos = self.writer.stream_position()?;
+        let bytes = encoder.finish();
+
+        self.writer.write_all(&bytes)?;
+        self.writer.reset();
+
+        // NOTE: The parity section is neither signed nor part of the content digest
+        self.toc.push(TocEntry {
+            name: SectionName::new(),
+            pos,
+            len: bytes.len() as u64,
+            flags: FLAG_PARITY,
+            ..Default::default()
+        });
+
+        Ok(())
+    }
+
+    /// Moves to the start of the final volume, if the archive is split into volumes,
+    /// and makes the positions of the entries relative to the volumes they start in.
+    fn start_final_volume(&mut self) -> std::io::Result<()> {
+        let Some(volume_size) = self.volume_size else {
+            return Ok(());
+        };
+
+        let pos = self.writer.stream_position()?;
+        self.final_volume_pos = pos.div_ceil(volume_size) * volume_size;
+        self.writer.seek(SeekFrom::Start(self.final_volume_pos))?;
+
+        for entry in &mut self.toc {
+            let volume = u32::try_from(entry.pos / volume_size).map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "too many volumes")
+            })?;
+
+            entry.volume = Some(volume);
+            entry.pos %= volume_size;
+            entry.update_flags();
+        }
+
+        Ok(())
+    }
+
+    /// Returns the position relative to the start of the final volume
+    /// (which is the start of the file, unless the archive is split into volumes).
+    fn volume_position(&mut self) -> std::io::Result<u64> {
+        Ok(self.writer.stream_position()? - self.final_volume_pos)
+    }
+
+    fn finish_archive(&mut self) -> crate::Result<WriteSummary> {
+        let result = self.write_archive_end();
+
+        if let Some(finish_volumes) = self.finish_volumes {
+            let volumes = self.get_mut();
+
+            match &result {
+                Ok(_) => finish_volumes(volumes, true)?,
+                Err(_) => {
+                    if let Err(e) = finish_volumes(volumes, false) {
+                        log::warn!("Failed to remove volumes: {e}");
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Writes everything following the sections, up to the trailer.
+    fn write_archive_end(&mut self) -> crate::Result<WriteSummary> {
         log::trace!("Finishing archive");
 
         self.append_toc_entry()?;
-        Self::append_trailer(&mut self.writer, &self.toc)?;
+
+        // NOTE: Discarded data that was not overwritten cannot be truncated, so the trailer
+        // stays at the end of the file, but it is zeroed to not leave stale section data around
+        let pos = self.writer.stream_position()?;
+        if pos < self.data_end {
+            std::io::copy(
+                &mut std::io::repeat(0).take(self.data_end - pos),
+                &mut self.writer,
+            )?;
+            self.writer.reset();
+        }
+
+        #[cfg(feature = "parity")]
+        self.append_parity()?;
+
+        self.start_final_volume()?;
+        let (toc_pos, toc_len, toc_checksum) = self.append_trailer()?;
         self.writer.flush()?;
 
-        Ok(())
+        let file_size = self.volume_position()?;
+
+        if self
+            .volume_size
+            .is_some_and(|volume_size| file_size > volume_size)
+        {
+            return Err(crate::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "table of contents does not fit into a volume",
+            )));
+        }
+
+        let content_digest = ContentDigest::compute(
+            self.toc
+                .iter()
+                .filter(|entry| !entry.is_parity())
+                .zip(self.section_hashes.iter().copied()),
+        );
+
+        Ok(WriteSummary {
+            toc: Toc::new(std::mem::take(&mut self.toc)),
+            toc_checksum,
+            toc_pos,
+            toc_len,
+            file_size,
+            content_digest,
+            app_id: self.extension.app_id,
+            toc_encoding: self.extension.toc_encoding,
+            redundant_toc: self.redundant_toc,
+        })
     }
 
     /// Finishes the file.
     ///
-    /// Returns the inner writer.
+    /// Returns a summary of the written archive.
     ///
     /// # Errors
     ///
     /// Returns error, if an IO error occurred.
-    pub fn into_inner(mut self) -> crate::Result<W> {
-        log::trace!("Finishing archive");
+    pub fn finish(mut self) -> crate::Result<WriteSummary> {
+        self.finish_archive()
+    }
 
-        self.append_toc_entry()?;
-        Self::append_trailer(&mut self.writer, &self.toc)?;
-        self.writer.flush()?;
+    /// Finishes the file, and signs it using the given key.
+    ///
+    /// The Ed25519 signature covers the trailer, the table of contents
+    /// and a SHA-256 digest of every section (including the header),
+    /// and can be checked using [`crate::Reader::verify_signature`].
+    ///
+    /// The signature is stored between the table of contents and the trailer,
+    /// so it is ignored by readers that do not check it.
+    ///
+    /// Note that rewriting the archive (e.g. using [`crate::ArchiveEditor`]) removes the signature.
+    ///
+    /// Returns a summary of the written archive.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    #[cfg(feature = "signing")]
+    pub fn finish_signed(mut self, signing_key: &SigningKey) -> crate::Result<WriteSummary> {
+        self.signing_key = Some(signing_key.clone());
+        self.finish()
+    }
+
+    /// Finishes the file.
+    ///
+    /// Returns the inner writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn into_inner(self) -> crate::Result<W> {
+        self.into_inner_with_summary().map(|(writer, _)| writer)
+    }
 
-        Ok(self.writer)
+    /// Finishes the file.
+    ///
+    /// Returns the inner writer, and a summary of the written archive.
+    ///
+    /// # Errors
+    ///
+    /// Returns error, if an IO error occurred.
+    pub fn into_inner_with_summary(mut self) -> crate::Result<(W, WriteSummary)> {
+        let summary = self.finish_archive()?;
+        Ok((self.writer.into_inner().into_inner(), summary))
     }
 }
 
@@ -148,7 +1469,7 @@ mod tests {
         let trailer = TrailerReader::from_reader(&mut reader)?;
         assert_eq!(0, trailer.toc_pos);
 
-        let toc = TocReader::from_reader(&mut reader, trailer.toc_pos, trailer.toc_checksum)?;
+        let toc = TocReader::from_reader(&mut reader, &trailer)?;
         assert_eq!(0, toc.len());
         assert!(toc.is_empty());
         assert!(toc.section(b"hello").is_none());
@@ -173,8 +1494,9 @@ mod tests {
         let mut reader = File::open(&path)?;
         let trailer = TrailerReader::from_reader(&mut reader)?;
         assert_eq!(data.len() as u64, trailer.toc_pos);
+        assert_eq!(FormatVersion::V1, trailer.version);
 
-        let toc = TocReader::from_reader(&mut reader, trailer.toc_pos, trailer.toc_checksum)?;
+        let toc = TocReader::from_reader(&mut reader, &trailer)?;
         assert_eq!(1, toc.len());
         assert!(toc.section(b"hello").is_none());
         assert!(toc.section(b"").is_some());
@@ -213,7 +1535,7 @@ mod tests {
             trailer.toc_pos,
         );
 
-        let toc = TocReader::from_reader(&mut reader, trailer.toc_pos, trailer.toc_checksum)?;
+        let toc = TocReader::from_reader(&mut reader, &trailer)?;
         assert_eq!(3, toc.len());
         assert!(toc.section(b"hello").is_none());
         assert!(toc.section(b"").is_some());
@@ -233,4 +1555,68 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn writer_header() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("file.sfa");
+
+        let mut file = File::create(&path)?;
+        let mut writer = Writer::from_writer(&mut file);
+        writer.write_header(b"header")?;
+        writer.start("section1")?;
+        writer.write_all(b"hello world")?;
+        writer.finish()?;
+        file.sync_all()?;
+        drop(file);
+
+        let mut reader = File::open(&path)?;
+        let trailer = TrailerReader::from_reader(&mut reader)?;
+        assert_eq!(FormatVersion::V2, trailer.version);
+
+        let toc = TocReader::from_reader(&mut reader, &trailer)?;
+        assert_eq!(1, toc.len());
+        assert_eq!(b"section1", &*toc[0].name);
+        assert_eq!(6, toc[0].pos);
+
+        let header = toc.header().unwrap();
+        assert_eq!(0, header.pos);
+        assert_eq!(6, header.len);
+        assert_eq!(FLAG_HEADER, header.flags);
+
+        Ok(())
+    }
+
+    #[test]
+    fn writer_summary() -> crate::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("file.sfa");
+
+        let mut file = File::create(&path)?;
+        let mut writer = Writer::from_writer(&mut file).use_app_id(*b"TEST", 1);
+        writer.write_header(b"header")?;
+        writer.start("section1")?;
+        writer.write_all(b"hello world")?;
+        writer.start("section2")?;
+        let summary = writer.finish()?;
+        file.sync_all()?;
+        drop(file);
+
+        assert_eq!(std::fs::metadata(&path)?.len(), summary.file_size());
+
+        let names = summary.section_sizes().collect::<Vec<_>>();
+        assert_eq!(vec![(&b"section1"[..], 11), (b"section2", 0)], names);
+        assert_eq!(6, summary.toc().header().unwrap().len());
+
+        let mut reader = File::open(&path)?;
+        let trailer = TrailerReader::from_reader(&mut reader)?;
+        assert_eq!(trailer.toc_pos, summary.toc_pos());
+        assert_eq!(trailer.toc_len, summary.toc_len());
+        assert_eq!(trailer.toc_checksum, summary.toc_checksum());
+
+        let toc = TocReader::from_reader(&mut reader, &trailer)?;
+        assert_eq!(&*toc, &**summary.toc());
+
+        Ok(())
+    }
 }
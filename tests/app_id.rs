@@ -0,0 +1,78 @@
+mod common;
+
+use common::{write_archive, CHERRY_PIE};
+use sfa::{AppId, Reader, ReaderOptions};
+
+#[test]
+pub fn app_id_match() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, None, CHERRY_PIE, |writer| {
+        writer.use_app_id(b"SEGMENT!", 7)
+    })?;
+
+    let reader = Reader::with_options(&path, &ReaderOptions::default().expect_app_id(b"SEGMENT!"))?;
+    assert_eq!(Some(AppId::from(b"SEGMENT!")), reader.app_id());
+    assert_eq!(b"SEGMENT!", reader.app_id().unwrap().as_bytes());
+    assert_eq!(Some(7), reader.app_version());
+    assert_eq!(1, reader.toc().len());
+
+    let reader = Reader::new(&path)?;
+    assert_eq!(Some(7), reader.app_version());
+
+    Ok(())
+}
+
+#[test]
+pub fn app_id_short() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, None, CHERRY_PIE, |writer| {
+        writer.use_app_id(b"BLOB", 7)
+    })?;
+
+    let reader = Reader::with_options(&path, &ReaderOptions::default().expect_app_id(b"BLOB"))?;
+    assert_eq!(b"BLOB", reader.app_id().unwrap().as_bytes());
+
+    Ok(())
+}
+
+#[test]
+pub fn app_id_mismatch() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, None, CHERRY_PIE, |writer| {
+        writer.use_app_id(b"BLOB", 7)
+    })?;
+
+    assert!(matches!(
+        Reader::with_options(&path, &ReaderOptions::default().expect_app_id(b"MNFT")),
+        Err(sfa::Error::AppIdMismatch {
+            got: Some(got),
+            ..
+        }) if got == AppId::from(b"BLOB"),
+    ));
+
+    assert!(matches!(
+        Reader::with_options(&path, &ReaderOptions::default().expect_app_id(b"BLOB0000")),
+        Err(sfa::Error::AppIdMismatch { .. }),
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn app_id_missing() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, None, CHERRY_PIE, |writer| writer)?;
+
+    assert!(Reader::new(&path)?.app_id().is_none());
+
+    assert!(matches!(
+        Reader::with_options(&path, &ReaderOptions::default().expect_app_id(b"BLOB")),
+        Err(sfa::Error::AppIdMismatch { got: None, .. }),
+    ));
+
+    Ok(())
+}
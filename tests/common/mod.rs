@@ -0,0 +1,62 @@
+// NOTE: Every test binary compiles this module, but only uses some of it
+#![allow(dead_code)]
+
+use sfa::{TocEntry, WriteSummary, Writer};
+use std::{
+    fs::File,
+    io::{Read, Seek, Write},
+    path::Path,
+};
+
+/// Section of the usual single-section test archive
+pub const CHERRY_PIE: &[(&str, &[u8])] = &[("Verse 1", b"Glazed eyes and cherry pie\n")];
+
+/// Writes the header (if any), and the given sections.
+pub fn write_sections<W, N, D>(
+    writer: &mut Writer<W>,
+    header: Option<&[u8]>,
+    sections: &[(N, D)],
+) -> std::io::Result<()>
+where
+    W: Write + Seek,
+    N: AsRef<[u8]>,
+    D: AsRef<[u8]>,
+{
+    if let Some(header) = header {
+        writer.write_header(header)?;
+    }
+
+    for (name, data) in sections {
+        writer.start(name.as_ref())?;
+        writer.write_all(data.as_ref())?;
+    }
+
+    Ok(())
+}
+
+/// Writes an archive file with the header (if any) and the given sections,
+/// using a writer with the options of `configure`.
+pub fn write_archive<N, D>(
+    path: &Path,
+    header: Option<&[u8]>,
+    sections: &[(N, D)],
+    configure: impl FnOnce(Writer<&mut File>) -> Writer<&mut File>,
+) -> Result<WriteSummary, sfa::Error>
+where
+    N: AsRef<[u8]>,
+    D: AsRef<[u8]>,
+{
+    let mut file = File::create(path)?;
+    let mut writer = configure(Writer::from_writer(&mut file));
+    write_sections(&mut writer, header, sections)?;
+    let summary = writer.finish()?;
+    file.sync_all()?;
+    Ok(summary)
+}
+
+/// Reads the data of a section of the given archive file.
+pub fn read_section(path: &Path, entry: &TocEntry) -> std::io::Result<Vec<u8>> {
+    let mut data = vec![];
+    entry.reader(path)?.read_to_end(&mut data)?;
+    Ok(data)
+}
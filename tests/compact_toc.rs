@@ -0,0 +1,143 @@
+mod common;
+
+use common::write_archive;
+use sfa::{ArchiveEditor, Reader, Writer};
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+fn sections() -> Vec<(String, String)> {
+    (0..1_000)
+        .map(|idx| {
+            (
+                format!("idx/level0/level1/item-{idx:0>5}"),
+                format!("value {idx}"),
+            )
+        })
+        .chain([(String::new(), String::new())])
+        .collect()
+}
+
+#[test]
+pub fn compact_toc_roundtrip() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let plain_path = dir.path().join("plain");
+    let compact_path = dir.path().join("compact");
+
+    write_archive(&plain_path, Some(b"header"), &sections(), |writer| writer)?;
+    write_archive(&compact_path, Some(b"header"), &sections(), |writer| {
+        writer.use_compact_toc()
+    })?;
+
+    assert!(std::fs::metadata(&compact_path)?.len() < std::fs::metadata(&plain_path)?.len() / 2);
+
+    let plain = Reader::new(&plain_path)?;
+    let compact = Reader::new(&compact_path)?;
+
+    assert_eq!(1_001, compact.toc().len());
+    assert_eq!(
+        plain.header().unwrap().pos(),
+        compact.header().unwrap().pos()
+    );
+
+    for (a, b) in plain.toc().iter().zip(compact.toc().iter()) {
+        assert_eq!(a.name(), b.name());
+        assert_eq!(a.pos(), b.pos());
+        assert_eq!(a.len(), b.len());
+    }
+
+    let entry = compact
+        .toc()
+        .section(b"idx/level0/level1/item-00123")
+        .unwrap();
+    let mut buf = String::new();
+    entry.reader(&compact_path)?.read_to_string(&mut buf)?;
+    assert_eq!("value 123", buf);
+
+    Ok(())
+}
+
+#[test]
+pub fn compact_toc_editor_keeps_encoding() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("compact");
+
+    write_archive(&path, Some(b"header"), &sections(), |writer| {
+        writer.use_compact_toc()
+    })?;
+    let len = std::fs::metadata(&path)?.len();
+
+    let mut editor = ArchiveEditor::open(&path)?;
+    editor.remove(b"")?;
+    editor.write()?;
+
+    assert!(std::fs::metadata(&path)?.len() < len + 100);
+    assert_eq!(1_000, Reader::new(&path)?.toc().len());
+
+    Ok(())
+}
+
+#[test]
+pub fn compact_toc_corrupted() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("compact");
+
+    write_archive(&path, Some(b"header"), &sections(), |writer| {
+        writer.use_compact_toc()
+    })?;
+
+    {
+        let mut file = File::options().read(true).write(true).open(&path)?;
+        file.seek(SeekFrom::End(-100))?;
+        file.write_all(b"garbage")?;
+        file.sync_all()?;
+    }
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::ChecksumMismatch { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn compact_toc_name_len_limit() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for compact in [false, true] {
+        let path = dir.path().join(format!("archive-{compact}"));
+        let longest = "a".repeat(65_535);
+
+        let mut file = File::create(&path)?;
+        let mut writer = Writer::from_writer(&mut file);
+
+        if compact {
+            writer = writer.use_compact_toc();
+        }
+
+        writer.start(longest.clone())?;
+        writer.write_all(b"hello")?;
+
+        // NOTE: Longer names could not be read back
+        assert_eq!(
+            std::io::ErrorKind::InvalidInput,
+            writer.start("a".repeat(65_536)).unwrap_err().kind(),
+        );
+
+        writer.start(format!("{longest}b").get(1..).unwrap())?;
+        writer.write_all(b"world")?;
+        writer.finish()?;
+        drop(file);
+
+        let reader = Reader::new(&path)?;
+        let toc = reader.toc();
+        assert_eq!(2, toc.len());
+        assert_eq!(longest.as_bytes(), toc[0].name());
+        assert_eq!(65_535, toc[1].name().len());
+        assert_eq!(5, toc[1].len());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use sfa::{ContentDigest, Reader, Writer};
+use std::io::{Cursor, Write};
+
+fn build(
+    all_options: bool,
+    header: Option<&[u8]>,
+    sections: &[(&str, &[u8])],
+) -> Result<(ContentDigest, ContentDigest), sfa::Error> {
+    let mut cursor = Cursor::new(vec![]);
+
+    let mut writer = Writer::from_writer(&mut cursor);
+
+    if all_options {
+        writer = writer
+            .use_compact_toc()
+            .use_section_checksums()
+            .use_leading_magic(*b"SEGMENT!")
+            .use_app_id(*b"TEST", 2);
+    }
+
+    if let Some(header) = header {
+        writer.write_header(header)?;
+    }
+
+    for (name, data) in sections {
+        writer.start(*name)?;
+        writer.write_all(data)?;
+    }
+
+    let written = writer.finish()?.content_digest();
+
+    let reader = Reader::from_reader(&mut cursor)?;
+    let read = reader.content_digest_with_reader(&mut cursor)?;
+
+    Ok((written, read))
+}
+
+#[test]
+pub fn content_digest_writer_matches_reader() -> Result<(), sfa::Error> {
+    let (written, read) = build(
+        false,
+        Some(b"header"),
+        &[("a", b"hello"), ("b", b"world"), ("c", b"")],
+    )?;
+    assert_eq!(written, read);
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    let mut file = std::fs::File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file);
+    writer.start("a")?;
+    writer.write_all(b"hello")?;
+    let written = writer.finish()?.content_digest();
+    drop(file);
+
+    assert_eq!(written, Reader::new(&path)?.content_digest()?);
+
+    Ok(())
+}
+
+#[test]
+pub fn content_digest_ignores_layout() -> Result<(), sfa::Error> {
+    let sections: &[(&str, &[u8])] = &[("a", b"hello"), ("b", b"world"), ("c/d", b"!")];
+    let (expected, _) = build(false, Some(b"header"), sections)?;
+
+    let reversed = sections.iter().rev().copied().collect::<Vec<_>>();
+
+    for (digest, _) in [
+        build(false, Some(b"header"), &reversed)?,
+        build(true, Some(b"header"), sections)?,
+    ] {
+        assert_eq!(expected, digest);
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn content_digest_covers_contents() -> Result<(), sfa::Error> {
+    let (expected, _) = build(false, None, &[("a", b"hello"), ("b", b"world")])?;
+
+    for (header, sections) in [
+        (None, &[("a", &b"hello"[..]), ("b", b"World")][..]),
+        (None, &[("a", b"hello"), ("c", b"world")]),
+        (None, &[("a", b"hellow"), ("b", b"orld")]),
+        (None, &[("a", b"hello"), ("b", b"world"), ("b", b"world")]),
+        (None, &[("a", b"hello")]),
+        (Some(&b"header"[..]), &[("a", b"hello"), ("b", b"world")]),
+    ] {
+        let (digest, read) = build(false, header, sections)?;
+        assert_eq!(digest, read);
+        assert_ne!(expected, digest, "{header:?} {sections:?}");
+    }
+
+    Ok(())
+}
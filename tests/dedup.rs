@@ -0,0 +1,159 @@
+mod common;
+
+use common::{read_section, write_archive};
+use sfa::{ArchiveEditor, ExtractOptions, Reader, Writer};
+use std::io::{Cursor, Write};
+
+fn blob() -> Vec<u8> {
+    (0..10_000).map(|x: u32| (x % 251) as u8).collect()
+}
+
+fn sections() -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("a", blob()),
+        ("b", b"hello".to_vec()),
+        ("dir/c", blob()),
+        ("empty", vec![]),
+        ("empty2", vec![]),
+        ("d", blob()),
+    ]
+}
+
+#[test]
+pub fn dedup_shares_identical_sections() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+    let plain_path = dir.path().join("plain");
+
+    write_archive(&path, Some(b"header"), &sections(), |writer| {
+        writer.use_section_checksums().use_deduplication()
+    })?;
+    write_archive(&plain_path, Some(b"header"), &sections(), |writer| {
+        writer.use_section_checksums()
+    })?;
+
+    // NOTE: The data of the last section is discarded as well, but cannot be truncated
+    let blob_len = blob().len() as u64;
+    assert_eq!(
+        std::fs::metadata(&plain_path)?.len() - blob_len,
+        std::fs::metadata(&path)?.len(),
+    );
+
+    let reader = Reader::new(&path)?;
+    let plain_reader = Reader::new(&plain_path)?;
+
+    let pos = |name: &[u8]| reader.toc().section(name).map(sfa::TocEntry::pos);
+    assert_eq!(pos(b"a"), pos(b"dir/c"));
+    assert_eq!(pos(b"a"), pos(b"d"));
+    assert_ne!(pos(b"a"), pos(b"b"));
+
+    for name in [&b"a"[..], b"b", b"dir/c", b"empty", b"d"] {
+        assert_eq!(
+            read_section(&plain_path, plain_reader.toc().section(name).unwrap())?,
+            read_section(&path, reader.toc().section(name).unwrap())?,
+        );
+    }
+
+    // NOTE: The content digest does not depend on section positions
+    assert_eq!(plain_reader.content_digest()?, reader.content_digest()?);
+
+    #[cfg(feature = "rayon")]
+    assert!(reader.par_verify().is_ok());
+
+    let out = dir.path().join("out");
+    let options = ExtractOptions::default().verify_checksums(true);
+    assert_eq!(6, reader.extract_to(&out, &options)?);
+    assert_eq!(blob(), std::fs::read(out.join("dir/c"))?);
+
+    Ok(())
+}
+
+#[test]
+pub fn dedup_kept_by_editor() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+    write_archive(&path, Some(b"header"), &sections(), |writer| {
+        writer.use_section_checksums().use_deduplication()
+    })?;
+
+    let size = std::fs::metadata(&path)?.len();
+
+    let mut editor = ArchiveEditor::open(&path)?;
+    editor.remove(b"a")?;
+    editor.write()?;
+
+    // NOTE: The data of the removed section is still used by the other sections
+    assert!(std::fs::metadata(&path)?.len() < size);
+
+    let reader = Reader::new(&path)?;
+    let pos = |name: &[u8]| reader.toc().section(name).map(sfa::TocEntry::pos);
+    assert!(reader.toc().section(b"a").is_none());
+    assert_eq!(pos(b"dir/c"), pos(b"d"));
+    assert_eq!(
+        blob(),
+        read_section(&path, reader.toc().section(b"d").unwrap())?
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn dedup_last_section() -> Result<(), sfa::Error> {
+    let mut writer = Writer::from_writer(Cursor::new(vec![])).use_deduplication();
+    writer.start("a")?;
+    writer.write_all(b"hello world")?;
+    writer.start("b")?;
+    writer.write_all(b"hello world")?;
+    let bytes = writer.into_inner()?.into_inner();
+
+    let reader = Reader::from_reader(&mut Cursor::new(bytes))?;
+    assert_eq!(2, reader.toc().len());
+    assert_eq!(reader.toc()[0].pos(), reader.toc()[1].pos());
+    assert_eq!(reader.toc()[0].len(), reader.toc()[1].len());
+
+    // NOTE: Discarded data would be found when recovering framed archives
+    let mut writer = Writer::from_writer(Cursor::new(vec![]))
+        .use_deduplication()
+        .use_section_frames();
+    assert!(writer.start("a").is_err());
+
+    // NOTE: Retrying must not silently skip deduplication
+    assert!(writer.start("a").is_err());
+    assert!(writer.write_all(b"hello world").is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn dedup_zeroes_discarded_data() -> Result<(), sfa::Error> {
+    let mut writer = Writer::from_writer(Cursor::new(vec![])).use_deduplication();
+    writer.start("a")?;
+    writer.write_all(&blob())?;
+    writer.start("b")?;
+    writer.write_all(&blob())?;
+    writer.start("c")?;
+    writer.write_all(b"short")?;
+    let bytes = writer.into_inner()?.into_inner();
+
+    let reader = Reader::from_reader(&mut Cursor::new(&bytes))?;
+    let c = reader.toc().section(b"c").expect("section should exist");
+
+    // NOTE: The short section overwrites the start of the discarded data,
+    // the rest of it is zeroed
+    let end = c.pos() + c.len();
+    let stale = bytes
+        .get(end as usize..(c.pos() as usize + blob().len()))
+        .expect("discarded data should be kept");
+    assert!(stale.iter().all(|&b| b == 0));
+
+    // NOTE: The discarded data is not found anywhere else
+    assert_eq!(
+        1,
+        bytes
+            .windows(blob().len())
+            .filter(|window| *window == blob())
+            .count(),
+    );
+
+    Ok(())
+}
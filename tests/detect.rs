@@ -0,0 +1,114 @@
+use sfa::{detect, ArchiveEditor, ArchiveKind, Reader, Writer};
+use std::{
+    fs::File,
+    io::{Read, Write},
+};
+
+#[test]
+pub fn detect_tagged() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file).use_leading_magic(*b"CHERRY01");
+    writer.start("Verse 1")?;
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+    file.sync_all()?;
+    drop(file);
+
+    let mut bytes = vec![];
+    File::open(&path)?.read_to_end(&mut bytes)?;
+    assert_eq!(b"SFA\x01\x08CHERRY01", &bytes[..13]);
+
+    assert_eq!(
+        Some(ArchiveKind::Tagged {
+            app_id: sfa::AppId::from(*b"CHERRY01")
+        }),
+        detect(File::open(&path)?)?,
+    );
+
+    let reader = Reader::new(&path)?;
+    let toc = reader.toc();
+    assert_eq!(1, toc.len());
+    assert_eq!(b"Verse 1", toc[0].name());
+    assert_eq!(13, toc[0].pos());
+
+    let mut editor = ArchiveEditor::open(&path)?;
+    editor.rename(b"Verse 1", "Verse 2")?;
+    editor.write()?;
+
+    assert_eq!(
+        Some(ArchiveKind::Tagged {
+            app_id: sfa::AppId::from(*b"CHERRY01")
+        }),
+        detect(File::open(&path)?)?,
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn detect_tagged_implicit_section() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file).use_leading_magic(*b"CHERRY01");
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+    file.sync_all()?;
+    drop(file);
+
+    let reader = Reader::new(&path)?;
+    let toc = reader.toc();
+    assert_eq!(1, toc.len());
+    assert_eq!(b"", toc[0].name());
+    assert_eq!(13, toc[0].pos());
+    assert_eq!(27, toc[0].len());
+
+    Ok(())
+}
+
+#[test]
+pub fn detect_tagged_app_id() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file)
+        .use_app_id(*b"CHRY", 1)
+        .use_leading_magic(*b"CHRY");
+    writer.start("Verse 1")?;
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+    file.sync_all()?;
+    drop(file);
+
+    let app_id = sfa::AppId::from(*b"CHRY");
+    assert_eq!(
+        Some(ArchiveKind::Tagged { app_id }),
+        detect(File::open(&path)?)?,
+    );
+    assert_eq!(Some(app_id), Reader::new(&path)?.app_id());
+
+    Ok(())
+}
+
+#[test]
+pub fn detect_untagged() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file);
+    writer.start("Verse 1")?;
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+    file.sync_all()?;
+    drop(file);
+
+    assert_eq!(Some(ArchiveKind::Untagged), detect(File::open(&path)?)?);
+
+    Ok(())
+}
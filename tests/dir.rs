@@ -0,0 +1,81 @@
+use sfa::{DirEntry, Reader, Writer};
+use std::{fs::File, io::Write};
+
+#[test]
+pub fn dir_listing() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("tree");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file).use_path_validation();
+
+    for name in ["idx/b", "data/x/1", "idx/a", "data/y", "README"] {
+        writer.start(name)?;
+        writer.write_all(name.as_bytes())?;
+    }
+
+    writer.finish()?;
+    drop(file);
+
+    let reader = Reader::new(&path)?;
+    let toc = reader.toc();
+
+    let root = toc
+        .list_dir(b"")
+        .map(|entry| (entry.file_name(), entry.is_dir()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![(&b"README"[..], false), (b"data", true), (b"idx", true)],
+        root,
+    );
+
+    let data = toc.list_dir(b"data").collect::<Vec<_>>();
+    assert_eq!(2, data.len());
+    assert!(matches!(data[0], DirEntry::Dir { path: b"data/x" }));
+    assert!(matches!(data[1], DirEntry::Section(entry) if entry.name() == b"data/y"));
+
+    let walked = toc.walk().map(|entry| entry.path()).collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            &b"README"[..],
+            b"data",
+            b"data/x",
+            b"data/x/1",
+            b"data/y",
+            b"idx",
+            b"idx/a",
+            b"idx/b",
+        ],
+        walked,
+    );
+
+    let idx = toc
+        .prefix(b"idx/")
+        .map(|entry| entry.name())
+        .collect::<Vec<_>>();
+    assert_eq!(vec![b"idx/b", b"idx/a"], idx);
+
+    Ok(())
+}
+
+#[test]
+pub fn dir_path_validation() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("tree");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file).use_path_validation();
+
+    for name in ["", "/etc/passwd", "a/../../b", "a//b", "a/", "a\\b"] {
+        let err = writer.start(name).unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+    }
+
+    writer.start("a/b")?;
+    writer.finish()?;
+    drop(file);
+
+    assert_eq!(1, Reader::new(&path)?.toc().len());
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::{read_section, write_archive};
+use sfa::{ArchiveEditor, Reader};
+
+const SECTIONS: &[(&str, &[u8])] = &[
+    ("Verse 1", b"Glazed eyes and cherry pie\n"),
+    ("Chorus", b"Youth is running out, we finally feel it now\n"),
+    ("Verse 2", b"Your phone glow face in the dark\n"),
+];
+
+#[test]
+pub fn editor_remove_rename_replace() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, None, SECTIONS, |writer| writer)?;
+
+    let mut editor = ArchiveEditor::open(&path)?;
+    editor.remove(b"Chorus")?;
+    editor.rename(b"Verse 2", "Outro")?;
+    editor.replace(b"Verse 1", &b"There's a hush now in our hearts\n"[..])?;
+    editor.push("Chorus 2", &b"My mind is changing, forever is fading\n"[..]);
+    editor.write()?;
+
+    let reader = Reader::new(&path)?;
+    let toc = reader.toc();
+    assert_eq!(3, toc.len());
+
+    assert_eq!(b"Verse 1", toc[0].name());
+    assert_eq!(
+        read_section(&path, &toc[0])?,
+        b"There's a hush now in our hearts\n"
+    );
+
+    assert_eq!(b"Outro", toc[1].name());
+    assert_eq!(
+        read_section(&path, &toc[1])?,
+        b"Your phone glow face in the dark\n"
+    );
+
+    assert_eq!(b"Chorus 2", toc[2].name());
+    assert_eq!(
+        read_section(&path, &toc[2])?,
+        b"My mind is changing, forever is fading\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn editor_reorder() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let dest = dir.path().join("cherry_pie_reordered");
+    write_archive(&path, None, SECTIONS, |writer| writer)?;
+
+    let mut editor = ArchiveEditor::open(&path)?;
+    editor.reorder([b"Verse 2"])?;
+    editor.move_to(b"Verse 1", 2)?;
+    editor.insert(0, "", &[][..])?;
+    assert_eq!(
+        vec![&b""[..], b"Verse 2", b"Chorus", b"Verse 1"],
+        editor.section_names().collect::<Vec<_>>(),
+    );
+    editor.write_to(&dest)?;
+
+    // Source archive is untouched
+    assert_eq!(3, Reader::new(&path)?.toc().len());
+
+    let reader = Reader::new(&dest)?;
+    let toc = reader.toc();
+    assert_eq!(4, toc.len());
+    assert_eq!(b"", toc[0].name());
+    assert_eq!(0, toc[0].len());
+    assert_eq!(b"Verse 2", toc[1].name());
+    assert_eq!(b"Chorus", toc[2].name());
+    assert_eq!(b"Verse 1", toc[3].name());
+    assert_eq!(
+        read_section(&dest, &toc[3])?,
+        b"Glazed eyes and cherry pie\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn editor_section_not_found() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, None, SECTIONS, |writer| writer)?;
+
+    let mut editor = ArchiveEditor::open(&path)?;
+    assert!(matches!(
+        editor.remove(b"Bridge"),
+        Err(sfa::Error::SectionNotFound { name }) if name == b"Bridge",
+    ));
+    assert!(matches!(
+        editor.rename(b"Bridge", "Outro"),
+        Err(sfa::Error::SectionNotFound { .. }),
+    ));
+    assert!(matches!(
+        editor.move_to(b"Chorus", 3),
+        Err(sfa::Error::IndexOutOfBounds { index: 3, len: 3 }),
+    ));
+    assert!(matches!(
+        editor.insert(4, "Bridge", &[][..]),
+        Err(sfa::Error::IndexOutOfBounds { index: 4, len: 3 }),
+    ));
+
+    Ok(())
+}
@@ -99,3 +99,30 @@ pub fn empty_section_name() -> Result<(), sfa::Error> {
 
     Ok(())
 }
+
+#[test]
+pub fn empty_first_section() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file);
+    writer.start("Intro")?;
+    writer.start("Verse 1")?;
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+    file.sync_all()?;
+    drop(file);
+
+    let reader = Reader::new(&path)?;
+    let toc = reader.toc();
+    assert_eq!(2, toc.len());
+
+    assert_eq!(b"Intro", toc[0].name());
+    assert_eq!(0, toc[0].len());
+
+    assert_eq!(b"Verse 1", toc[1].name());
+    assert_eq!(27, toc[1].len());
+
+    Ok(())
+}
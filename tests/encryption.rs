@@ -0,0 +1,155 @@
+#![cfg(feature = "encryption")]
+
+use sfa::{ArchiveEditor, EncryptionKey, Reader, Writer};
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+const KEY: [u8; 32] = [42; 32];
+
+#[test]
+pub fn encryption_simple() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let key = EncryptionKey::new(3, KEY);
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file)
+        .use_encryption(key.clone())
+        .use_encryption_chunk_size(8);
+    writer.write_header(b"plain header")?;
+    writer.start("Verse 1")?;
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.start("empty")?;
+    writer.finish()?;
+    file.sync_all()?;
+    drop(file);
+
+    let raw = std::fs::read(&path)?;
+    assert!(raw.windows(12).any(|w| w == b"plain header"));
+    assert!(!raw.windows(11).any(|w| w == b"cherry pie\n"));
+
+    let reader = Reader::new(&path)?;
+    let toc = reader.toc();
+    assert_eq!(2, toc.len());
+
+    let encryption = toc[0].encryption().unwrap();
+    assert_eq!(3, encryption.key_id());
+    assert_eq!(8, encryption.chunk_size());
+    assert_eq!(27, encryption.plaintext_len());
+    assert_eq!(27 + 4 * 16, toc[0].len());
+
+    let mut reader = toc[0].decrypting_reader(&path, &key)?;
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf)?;
+    assert_eq!("Glazed eyes and cherry pie\n", buf);
+
+    reader.seek(SeekFrom::Start(16))?;
+    buf.clear();
+    reader.read_to_string(&mut buf)?;
+    assert_eq!("cherry pie\n", buf);
+
+    let mut reader = toc[1].decrypting_reader(&path, &key)?;
+    assert_eq!(0, reader.read_to_end(&mut vec![])?);
+
+    Ok(())
+}
+
+#[test]
+pub fn encryption_wrong_key() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file).use_encryption(EncryptionKey::new(3, KEY));
+    writer.start("Verse 1")?;
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+    drop(file);
+
+    let reader = Reader::new(&path)?;
+    let entry = &reader.toc()[0];
+
+    assert!(matches!(
+        entry.decrypting_reader(&path, &EncryptionKey::new(4, KEY)),
+        Err(sfa::Error::EncryptionKeyMismatch {
+            expected: 3,
+            got: 4,
+            ..
+        }),
+    ));
+
+    let mut reader = entry.decrypting_reader(&path, &EncryptionKey::new(3, [0; 32]))?;
+    let err = reader.read_to_end(&mut vec![]).unwrap_err();
+    assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+
+    Ok(())
+}
+
+#[test]
+pub fn encryption_not_encrypted() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file);
+    writer.start("Verse 1")?;
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+    drop(file);
+
+    let reader = Reader::new(&path)?;
+    let entry = &reader.toc()[0];
+    assert!(entry.encryption().is_none());
+
+    assert!(matches!(
+        entry.decrypting_reader(&path, &EncryptionKey::new(3, KEY)),
+        Err(sfa::Error::NotEncrypted { .. }),
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn encryption_editor_keeps_encrypted_sections() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let key = EncryptionKey::new(3, KEY);
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file).use_encryption(key.clone());
+    writer.start("Verse 1")?;
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.start("Verse 2")?;
+    writer.write_all(b"Lazy ways, a lazy smile\n")?;
+    writer.finish()?;
+    drop(file);
+
+    let mut editor = ArchiveEditor::open(&path)?;
+
+    // NOTE: The name of an encrypted section is authenticated
+    assert!(matches!(
+        editor.rename(b"Verse 2", "Chorus"),
+        Err(sfa::Error::Io(e)) if e.kind() == std::io::ErrorKind::InvalidInput,
+    ));
+
+    editor.remove(b"Verse 1")?;
+    editor.push("plain", &b"not encrypted"[..]);
+    editor.write()?;
+
+    let reader = Reader::new(&path)?;
+    let toc = reader.toc();
+    assert_eq!(2, toc.len());
+    assert!(toc[1].encryption().is_none());
+
+    let mut buf = String::new();
+    toc[0]
+        .decrypting_reader(&path, &key)?
+        .read_to_string(&mut buf)?;
+    assert_eq!("Lazy ways, a lazy smile\n", buf);
+
+    Ok(())
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::{write_archive, CHERRY_PIE};
+use sfa::Reader;
+use std::{
+    fs::File,
+    io::{Seek, SeekFrom, Write},
+    path::Path,
+};
+
+fn patch(path: &Path, pos: SeekFrom, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = File::options().write(true).open(path)?;
+    file.seek(pos)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+#[test]
+pub fn error_truncated() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    std::fs::write(&path, b"SFA!")?;
+
+    let err = Reader::new(&path).err().expect("should fail");
+    assert!(matches!(
+        err,
+        sfa::Error::TruncatedFile { file_size: 4, .. }
+    ));
+    assert_eq!(Some(&*path), err.context().and_then(|c| c.path()));
+
+    Ok(())
+}
+
+#[test]
+pub fn error_invalid_trailer_magic() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    std::fs::write(&path, [7; 100])?;
+
+    let err = Reader::new(&path).err().expect("should fail");
+    assert!(matches!(err, sfa::Error::InvalidTrailerMagic { .. }));
+    assert_eq!(Some(62), err.context().and_then(sfa::ErrorContext::offset));
+
+    Ok(())
+}
+
+#[test]
+pub fn error_unsupported_version() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, None, CHERRY_PIE, |writer| writer)?;
+    patch(&path, SeekFrom::End(-34), &[0x7F])?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::VersionUnsupported { found: 0x7F, .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn error_unsupported_checksum_type() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    write_archive(&path, None, CHERRY_PIE, |writer| writer)?;
+    patch(&path, SeekFrom::End(-33), &[0x3])?;
+
+    assert!(matches!(
+        Reader::new(&path),
+        Err(sfa::Error::UnsupportedChecksumType { found: 0x3, .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn error_invalid_toc_magic() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let toc_pos = write_archive(&path, None, CHERRY_PIE, |writer| writer)?.toc_pos();
+    patch(&path, SeekFrom::Start(toc_pos), b"COT!")?;
+
+    let err = Reader::new(&path).err().expect("should fail");
+    assert!(matches!(err, sfa::Error::InvalidTocMagic { .. }));
+    assert_eq!(
+        Some(toc_pos),
+        err.context().and_then(sfa::ErrorContext::offset)
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn error_truncated_toc() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+    let toc_pos = write_archive(&path, None, CHERRY_PIE, |writer| writer)?.toc_pos();
+
+    // NOTE: The table of contents claims more entries than the file holds
+    patch(&path, SeekFrom::Start(toc_pos + 4), &[0xFF, 0xFF, 0, 0])?;
+
+    let err = Reader::new(&path).err().expect("should fail");
+    assert!(matches!(err, sfa::Error::TruncatedFile { .. }));
+
+    let context = err.context().expect("should have context");
+    assert_eq!(Some(toc_pos), context.offset());
+    assert_eq!(Some(&*path), context.path());
+
+    Ok(())
+}
+
+#[test]
+pub fn error_display() -> Result<(), sfa::Error> {
+    let err = Reader::new("test_fixture/cherry_pie_broken")
+        .err()
+        .expect("should fail");
+
+    let msg = err.to_string();
+    assert!(msg.starts_with("checksum mismatch"), "{msg}");
+    assert!(msg.contains("cherry_pie_broken"), "{msg}");
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+mod common;
+
+use common::write_archive;
+use sfa::{ExtractOptions, OnCollision, Reader, SectionAttributes, Writer};
+use std::{
+    fs::File,
+    io::{Seek, SeekFrom, Write},
+    time::{Duration, SystemTime},
+};
+
+#[test]
+pub fn extract_roundtrip() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_234_567_890);
+
+    {
+        let mut file = File::create(&path)?;
+        let mut writer = Writer::from_writer(&mut file).use_section_checksums();
+        writer.write_header(b"header")?;
+        writer.start("README")?;
+        writer.write_all(b"hello")?;
+        writer.set_attributes(
+            SectionAttributes::default()
+                .with_mode(0o640)
+                .with_mtime(mtime),
+        )?;
+        writer.start("data/x/1")?;
+        writer.write_all(b"one")?;
+        writer.start("data/y")?;
+        writer.finish()?;
+        file.sync_all()?;
+    }
+
+    let reader = Reader::new(&path)?;
+
+    let readme = reader.toc().section(b"README").unwrap();
+    assert!(readme.checksum().is_some());
+    assert_eq!(Some(0o640), readme.attributes().unwrap().mode());
+    assert_eq!(Some(mtime), readme.attributes().unwrap().mtime());
+
+    let out = dir.path().join("out");
+    let count = reader.extract_to(&out, &ExtractOptions::default().verify_checksums(true))?;
+    assert_eq!(3, count);
+
+    assert_eq!(b"hello", &*std::fs::read(out.join("README"))?);
+    assert_eq!(b"one", &*std::fs::read(out.join("data/x/1"))?);
+    assert!(std::fs::read(out.join("data/y"))?.is_empty());
+
+    let metadata = std::fs::metadata(out.join("README"))?;
+    assert_eq!(mtime, metadata.modified()?);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(0o640, metadata.permissions().mode() & 0o777);
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn extract_unsafe_names() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    for name in ["../escape", "/etc/passwd", "a/../../b", "a//b", "a\\..\\b"] {
+        let path = dir.path().join("archive");
+        write_archive(
+            &path,
+            Some(b"header"),
+            &[("fine", b"fine"), (name, b"evil")],
+            |writer| writer.use_section_checksums(),
+        )?;
+
+        let out = dir.path().join("out");
+        let reader = Reader::new(&path)?;
+
+        assert!(
+            matches!(
+                reader.extract_to(&out, &ExtractOptions::default()),
+                Err(sfa::Error::UnsafeSectionName { .. }),
+            ),
+            "{name:?} should be refused",
+        );
+
+        // NOTE: Nothing is written if any name is unsafe
+        assert!(!out.join("fine").exists());
+    }
+
+    assert!(!dir.path().join("escape").exists());
+
+    Ok(())
+}
+
+#[test]
+pub fn extract_skips_unnamed_section() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file);
+    writer.write_all(b"implicit")?;
+    writer.start("a")?;
+    writer.write_all(b"hello")?;
+    writer.finish()?;
+    drop(file);
+
+    let reader = Reader::new(&path)?;
+    assert_eq!(b"", reader.toc()[0].name());
+
+    let out = dir.path().join("out");
+    assert_eq!(1, reader.extract_to(&out, &ExtractOptions::default())?);
+    assert_eq!(b"hello", &*std::fs::read(out.join("a"))?);
+    assert_eq!(1, std::fs::read_dir(&out)?.count());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+pub fn extract_symlink_escape() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+    write_archive(
+        &path,
+        Some(b"header"),
+        &[("link/file", b"evil")],
+        |writer| writer.use_section_checksums(),
+    )?;
+
+    let outside = dir.path().join("outside");
+    std::fs::create_dir(&outside)?;
+
+    let out = dir.path().join("out");
+    std::fs::create_dir(&out)?;
+    std::os::unix::fs::symlink(&outside, out.join("link"))?;
+
+    let reader = Reader::new(&path)?;
+
+    assert!(matches!(
+        reader.extract_to(
+            &out,
+            &ExtractOptions::default().on_collision(OnCollision::Overwrite)
+        ),
+        Err(sfa::Error::UnsafeSectionName { .. }),
+    ));
+    assert!(!outside.join("file").exists());
+
+    Ok(())
+}
+
+#[test]
+pub fn extract_collisions() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+    write_archive(
+        &path,
+        Some(b"header"),
+        &[("a", b"new"), ("b", b"new")],
+        |writer| writer.use_section_checksums(),
+    )?;
+
+    let out = dir.path().join("out");
+    std::fs::create_dir(&out)?;
+    std::fs::write(out.join("a"), b"old")?;
+
+    let reader = Reader::new(&path)?;
+
+    match reader.extract_to(&out, &ExtractOptions::default()) {
+        Err(sfa::Error::Io(e)) => assert_eq!(std::io::ErrorKind::AlreadyExists, e.kind()),
+        other => panic!("expected collision, got {other:?}"),
+    }
+    assert_eq!(b"old", &*std::fs::read(out.join("a"))?);
+
+    let count = reader.extract_to(
+        &out,
+        &ExtractOptions::default().on_collision(OnCollision::Skip),
+    )?;
+    assert_eq!(1, count);
+    assert_eq!(b"old", &*std::fs::read(out.join("a"))?);
+    assert_eq!(b"new", &*std::fs::read(out.join("b"))?);
+
+    let count = reader.extract_to(
+        &out,
+        &ExtractOptions::default().on_collision(OnCollision::Overwrite),
+    )?;
+    assert_eq!(2, count);
+    assert_eq!(b"new", &*std::fs::read(out.join("a"))?);
+
+    Ok(())
+}
+
+#[test]
+pub fn extract_checksum_mismatch() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+    write_archive(&path, Some(b"header"), &[("a", b"hello world")], |writer| {
+        writer.use_section_checksums()
+    })?;
+
+    let pos = Reader::new(&path)?.toc().section(b"a").unwrap().pos();
+
+    {
+        let mut file = File::options().write(true).open(&path)?;
+        file.seek(SeekFrom::Start(pos))?;
+        file.write_all(b"j")?;
+        file.sync_all()?;
+    }
+
+    let reader = Reader::new(&path)?;
+    let out = dir.path().join("out");
+
+    match reader.extract_to(&out, &ExtractOptions::default().verify_checksums(true)) {
+        Err(sfa::Error::ChecksumMismatch { context, .. }) => {
+            assert_eq!(Some(&b"a"[..]), context.section_name());
+        }
+        other => panic!("expected checksum mismatch, got {other:?}"),
+    }
+
+    // NOTE: Partially extracted files are removed
+    assert!(!out.join("a").exists());
+
+    // NOTE: Without verification, the data is extracted as is
+    reader.extract_to(&out, &ExtractOptions::default())?;
+    assert_eq!(b"jello world", &*std::fs::read(out.join("a"))?);
+
+    Ok(())
+}
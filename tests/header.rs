@@ -0,0 +1,136 @@
+use sfa::{ArchiveEditor, Reader, Writer};
+use std::{
+    fs::File,
+    io::{Read, Write},
+};
+
+#[test]
+pub fn header_simple() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file);
+    writer.write_header(b"PIE\x01")?;
+    writer.start("")?;
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.start("Chorus")?;
+    writer.write_all(b"Youth is running out, we finally feel it now\n")?;
+    writer.finish()?;
+    file.sync_all()?;
+    drop(file);
+
+    let mut file = File::open(&path)?;
+    let mut magic = [0; 4];
+    file.read_exact(&mut magic)?;
+    assert_eq!(b"PIE\x01", &magic);
+
+    let reader = Reader::new(&path)?;
+
+    let header = reader.header().expect("should have header");
+    assert!(header.is_header());
+    assert_eq!(0, header.pos());
+    assert_eq!(4, header.len());
+
+    let toc = reader.toc();
+    assert_eq!(2, toc.len());
+    assert!(!toc[0].is_header());
+    assert_eq!(b"", toc[0].name());
+    assert_eq!(4, toc[0].pos());
+    assert_eq!(b"Chorus", toc[1].name());
+
+    let bytes = toc[0]
+        .buf_reader(&path)?
+        .bytes()
+        .collect::<std::io::Result<Vec<_>>>()?;
+    assert_eq!(bytes, b"Glazed eyes and cherry pie\n");
+
+    Ok(())
+}
+
+#[test]
+pub fn header_implicit_section() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file);
+    writer.write_header(b"PIE\x01")?;
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+    file.sync_all()?;
+    drop(file);
+
+    let reader = Reader::new(&path)?;
+    assert_eq!(
+        4,
+        reader.header().map(sfa::TocEntry::len).unwrap_or_default()
+    );
+
+    let toc = reader.toc();
+    assert_eq!(1, toc.len());
+    assert_eq!(b"", toc[0].name());
+    assert_eq!(4, toc[0].pos());
+    assert_eq!(27, toc[0].len());
+
+    Ok(())
+}
+
+#[test]
+pub fn header_after_data() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file);
+    writer.start("Verse 1")?;
+    assert!(writer.write_header(b"PIE\x01").is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn header_no_header() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file);
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+    file.sync_all()?;
+    drop(file);
+
+    let reader = Reader::new(&path)?;
+    assert!(reader.header().is_none());
+    assert_eq!(1, reader.toc().len());
+
+    Ok(())
+}
+
+#[test]
+pub fn header_editor() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file);
+    writer.write_header(b"PIE\x01")?;
+    writer.start("Verse 1")?;
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.finish()?;
+    file.sync_all()?;
+    drop(file);
+
+    let mut editor = ArchiveEditor::open(&path)?;
+    editor.rename(b"Verse 1", "Verse 2")?;
+    editor.write()?;
+
+    let reader = Reader::new(&path)?;
+    let header = reader.header().expect("should have header");
+    assert_eq!(0, header.pos());
+    assert_eq!(4, header.len());
+    assert_eq!(b"Verse 2", reader.toc()[0].name());
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use common::write_sections;
+use sfa::{MultiVolumeReader, Reader, Writer};
+use std::{io::Read, io::Write, path::Path};
+
+const VOLUME_SIZE: u64 = 1_000;
+
+fn section_data(idx: usize, len: usize) -> Vec<u8> {
+    (0..len).map(|x| (x * (idx + 1)) as u8).collect()
+}
+
+const LENS: &[usize] = &[10, 2_500, 0, 990, 77];
+
+fn sections() -> Vec<(String, Vec<u8>)> {
+    LENS.iter()
+        .enumerate()
+        .map(|(idx, len)| (format!("section{idx}"), section_data(idx, *len)))
+        .collect()
+}
+
+fn read_section(reader: &MultiVolumeReader, name: &[u8]) -> std::io::Result<Vec<u8>> {
+    let entry = reader.toc().section(name).expect("section should exist");
+
+    let mut data = vec![];
+    reader.reader(entry)?.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn volume_path(path: &Path, volume: u32) -> std::path::PathBuf {
+    format!("{}.{:03}", path.display(), volume + 1).into()
+}
+
+#[test]
+pub fn multi_volume_roundtrip() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive.sfa");
+
+    for frames in [false, true] {
+        let mut writer = Writer::new_multi_volume(&path, VOLUME_SIZE)?
+            .use_app_id(*b"SPLT", 1)
+            .use_compact_toc()
+            .use_redundant_toc();
+
+        if frames {
+            writer = writer.use_section_frames();
+        }
+
+        write_sections(&mut writer, Some(b"header"), &sections())?;
+        let (volumes, summary) = writer.into_inner_with_summary()?;
+
+        let reader = MultiVolumeReader::new(&path)?;
+        assert_eq!(u64::from(reader.volume_count()), volumes.volume_count());
+        assert_eq!(Some(sfa::AppId::from(*b"SPLT")), reader.app_id());
+        assert_eq!(&**summary.toc(), &**reader.toc());
+        assert_eq!(LENS.len(), reader.toc().len());
+
+        // NOTE: All volumes but the final one are full, except the one before it
+        for volume in 0..reader.volume_count() {
+            let len = std::fs::metadata(volume_path(&path, volume))?.len();
+            assert!(len <= VOLUME_SIZE);
+
+            if volume + 2 < reader.volume_count() {
+                assert_eq!(VOLUME_SIZE, len);
+            }
+        }
+
+        let final_volume = reader.volume_count() - 1;
+        assert!(!volume_path(&path, final_volume + 1).try_exists()?);
+        assert_eq!(
+            summary.file_size(),
+            std::fs::metadata(volume_path(&path, final_volume))?.len(),
+        );
+
+        for (idx, len) in LENS.iter().enumerate() {
+            let name = format!("section{idx}");
+            assert_eq!(
+                section_data(idx, *len),
+                read_section(&reader, name.as_bytes())?
+            );
+
+            let entry = reader.toc().section(name.as_bytes()).expect("should exist");
+            assert!(entry
+                .volume()
+                .is_some_and(|volume| volume < final_volume || *len == 0));
+        }
+
+        let header = reader.header().expect("should have header");
+        let mut data = vec![];
+        reader.reader(header)?.read_to_end(&mut data)?;
+        assert_eq!(b"header", &*data);
+
+        // NOTE: The final volume cannot be read on its own
+        assert!(matches!(
+            Reader::new(volume_path(&path, final_volume)),
+            Err(sfa::Error::MultiVolume { .. }),
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn multi_volume_replaces_previous_volumes() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive.sfa");
+
+    let mut writer = Writer::new_multi_volume(&path, 300)?;
+    write_sections(&mut writer, Some(b"header"), &sections())?;
+    writer.finish()?;
+
+    let mut writer = Writer::new_multi_volume(&path, VOLUME_SIZE)?;
+    writer.start("a")?;
+    writer.write_all(b"hello")?;
+
+    // NOTE: Written data can be made durable before finishing the archive
+    writer.flush()?;
+    writer.get_mut().sync_all()?;
+
+    writer.finish()?;
+
+    let reader = MultiVolumeReader::new(&path)?;
+    assert_eq!(2, reader.volume_count());
+    assert_eq!(b"hello", &*read_section(&reader, b"a")?);
+
+    Ok(())
+}
+
+#[test]
+pub fn multi_volume_toc_too_large() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive.sfa");
+
+    let mut writer = Writer::new_multi_volume(&path, 50)?;
+    write_sections(&mut writer, Some(b"header"), &sections())?;
+    assert!(writer.get_mut().volume_count() > 1);
+    assert!(writer.finish().is_err());
+
+    // NOTE: No incomplete archive is left behind
+    assert!(!volume_path(&path, 0).exists());
+    assert_eq!(0, std::fs::read_dir(dir.path())?.count());
+
+    assert!(Writer::new_multi_volume(&path, 0).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use sfa::{ExtractOptions, PackOptions, Reader, Writer};
+use std::{
+    fs::File,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+fn create_tree(root: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(root.join("src/nested"))?;
+    std::fs::create_dir_all(root.join("target"))?;
+    std::fs::create_dir_all(root.join("empty"))?;
+
+    std::fs::write(root.join("README.md"), b"readme")?;
+    std::fs::write(root.join("src/lib.rs"), b"lib")?;
+    std::fs::write(root.join("src/nested/mod.rs"), b"mod")?;
+    std::fs::write(root.join("src/a.txt"), b"a")?;
+    std::fs::write(root.join("target/out"), b"out")?;
+
+    Ok(())
+}
+
+#[test]
+pub fn pack_dir_sorted() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let root = dir.path().join("tree");
+    create_tree(&root)?;
+
+    let path = dir.path().join("archive");
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file).use_path_validation();
+    let count = writer.add_dir_recursive(&root, &PackOptions::default())?;
+    writer.finish()?;
+    drop(file);
+
+    assert_eq!(5, count);
+
+    let reader = Reader::new(&path)?;
+    let names = reader
+        .toc()
+        .iter()
+        .map(|entry| String::from_utf8_lossy(entry.name()).into_owned())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        vec![
+            "README.md",
+            "src/a.txt",
+            "src/lib.rs",
+            "src/nested/mod.rs",
+            "target/out",
+        ],
+        names,
+    );
+
+    assert!(reader
+        .toc()
+        .iter()
+        .all(|entry| entry.attributes().is_none()));
+
+    Ok(())
+}
+
+#[test]
+pub fn pack_dir_filters() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let root = dir.path().join("tree");
+    create_tree(&root)?;
+
+    let path = dir.path().join("archive");
+
+    let options = PackOptions::default()
+        .exclude(|path| path.starts_with("target"))
+        .include(|path| path.extension().is_some_and(|ext| ext == "rs"));
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file);
+    let count = writer.add_dir_recursive(&root, &options)?;
+    writer.finish()?;
+    drop(file);
+
+    assert_eq!(2, count);
+
+    let reader = Reader::new(&path)?;
+    assert!(reader.toc().section(b"src/lib.rs").is_some());
+    assert!(reader.toc().section(b"src/nested/mod.rs").is_some());
+
+    Ok(())
+}
+
+#[test]
+pub fn pack_dir_attributes_roundtrip() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let root = dir.path().join("tree");
+    create_tree(&root)?;
+
+    let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+    File::options()
+        .write(true)
+        .open(root.join("src/lib.rs"))?
+        .set_modified(mtime)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(
+            root.join("src/lib.rs"),
+            std::fs::Permissions::from_mode(0o600),
+        )?;
+    }
+
+    let path = dir.path().join("archive");
+
+    let options = PackOptions::default().record_mode(true).record_mtime(true);
+
+    let mut file = File::create(&path)?;
+    let mut writer = Writer::from_writer(&mut file);
+    writer.add_dir_recursive(&root, &options)?;
+    writer.add_file("extra/README.md", root.join("README.md"))?;
+    writer.finish()?;
+    drop(file);
+
+    let reader = Reader::new(&path)?;
+
+    let attributes = reader
+        .toc()
+        .section(b"src/lib.rs")
+        .unwrap()
+        .attributes()
+        .unwrap();
+    assert_eq!(Some(mtime), attributes.mtime());
+
+    #[cfg(unix)]
+    assert_eq!(Some(0o600), attributes.mode());
+
+    // NOTE: add_file does not record attributes by itself
+    assert!(reader
+        .toc()
+        .section(b"extra/README.md")
+        .unwrap()
+        .attributes()
+        .is_none());
+
+    let out = dir.path().join("out");
+    assert_eq!(6, reader.extract_to(&out, &ExtractOptions::default())?);
+
+    for name in ["README.md", "src/a.txt", "src/lib.rs", "src/nested/mod.rs"] {
+        assert_eq!(
+            std::fs::read(root.join(name))?,
+            std::fs::read(out.join(name))?
+        );
+    }
+    assert_eq!(b"readme", &*std::fs::read(out.join("extra/README.md"))?);
+    assert_eq!(
+        mtime,
+        std::fs::metadata(out.join("src/lib.rs"))?.modified()?
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn pack_dir_not_a_directory() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let file_path = dir.path().join("file");
+    std::fs::write(&file_path, b"hello")?;
+
+    let mut writer = Writer::from_writer(std::io::Cursor::new(vec![]));
+
+    assert_eq!(
+        std::io::ErrorKind::InvalidInput,
+        writer
+            .add_dir_recursive(&file_path, &PackOptions::default())
+            .unwrap_err()
+            .kind(),
+    );
+
+    Ok(())
+}
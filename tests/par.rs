@@ -0,0 +1,128 @@
+#![cfg(feature = "rayon")]
+
+mod common;
+
+use sfa::Reader;
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+fn write_archive(path: &Path) -> Result<(), sfa::Error> {
+    let sections = (0..32u8)
+        .map(|idx| {
+            (
+                format!("section{idx:02}"),
+                vec![idx; 1_000 * usize::from(idx)],
+            )
+        })
+        .collect::<Vec<_>>();
+
+    common::write_archive(path, Some(b"header"), &sections, |writer| {
+        writer.use_section_checksums()
+    })?;
+    Ok(())
+}
+
+fn corrupt(path: &Path, pos: u64) -> std::io::Result<()> {
+    let mut file = File::options().write(true).open(path)?;
+    file.seek(SeekFrom::Start(pos))?;
+    file.write_all(b"!")?;
+    file.sync_all()
+}
+
+#[test]
+pub fn par_verify() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+    write_archive(&path)?;
+
+    let reader = Reader::new(&path)?;
+    reader.par_verify().unwrap();
+
+    let broken = ["section03", "section17", "section31"];
+
+    for name in broken {
+        corrupt(
+            &path,
+            reader.toc().section(name.as_bytes()).unwrap().pos() + 1,
+        )?;
+    }
+
+    let errors = reader.par_verify().unwrap_err();
+    assert_eq!(broken.len(), errors.len());
+
+    // NOTE: Errors are reported in table of contents order
+    for (error, name) in errors.iter().zip(broken) {
+        match error {
+            sfa::Error::ChecksumMismatch { context, .. } => {
+                assert_eq!(Some(name.as_bytes()), context.section_name());
+                assert_eq!(Some(path.as_path()), context.path());
+            }
+            other => panic!("expected checksum mismatch, got {other:?}"),
+        }
+    }
+
+    let errors = reader
+        .par_verify_with_file(&File::open(&path)?)
+        .unwrap_err();
+    assert_eq!(broken.len(), errors.len());
+
+    Ok(())
+}
+
+#[test]
+pub fn par_for_each_section() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+    write_archive(&path)?;
+
+    let reader = Reader::new(&path)?;
+    let sums = Mutex::new(vec![]);
+
+    reader
+        .par_for_each_section(|entry, section| {
+            let mut data = vec![];
+            section.read_to_end(&mut data)?;
+            assert_eq!(entry.len(), data.len() as u64);
+
+            // NOTE: Positions are relative to the section
+            if !data.is_empty() {
+                section.seek(SeekFrom::End(-1))?;
+                let mut last = [0];
+                section.read_exact(&mut last)?;
+                assert_eq!(data.last(), Some(&last[0]));
+            }
+
+            let sum = data.iter().map(|&b| u64::from(b)).sum::<u64>();
+            sums.lock().unwrap().push((entry.name().to_vec(), sum));
+            Ok(())
+        })
+        .unwrap();
+
+    let mut sums = sums.into_inner().unwrap();
+    sums.sort();
+
+    assert_eq!(32, sums.len());
+    for (idx, (name, sum)) in (0..32u64).zip(sums) {
+        assert_eq!(format!("section{idx:02}").as_bytes(), name);
+        assert_eq!(idx * idx * 1_000, sum);
+    }
+
+    let errors = reader
+        .par_for_each_section(|entry, _| {
+            if entry.name().ends_with(b"5") {
+                Err(sfa::Error::SectionNotFound {
+                    name: entry.name().to_vec(),
+                })
+            } else {
+                Ok(())
+            }
+        })
+        .unwrap_err();
+    assert_eq!(3, errors.len());
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use sfa::{AssemblyOrder, ParallelWriter, Reader, SectionAttributes, Writer};
+use std::{fs::File, io::Write};
+
+#[test]
+pub fn parallel_writer_threads() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+    let spill_dir = dir.path().join("spill");
+    std::fs::create_dir(&spill_dir)?;
+
+    let file = File::create(&path)?;
+    let writer = ParallelWriter::new(Writer::from_writer(file).use_section_checksums())
+        .use_spill_threshold(1_000)
+        .use_spill_dir(&spill_dir);
+
+    std::thread::scope(|scope| {
+        let handles = (0..16u8)
+            .rev()
+            .map(|idx| {
+                let mut section = writer.section(format!("section{idx:02}"));
+
+                scope.spawn(move || {
+                    // NOTE: Every other section is large enough to be spilled
+                    let len = if idx % 2 == 0 { 10 } else { 5_000 };
+
+                    for _ in 0..len {
+                        section.write_all(&[idx])?;
+                    }
+
+                    section.finish()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap()?;
+        }
+
+        Ok::<_, std::io::Error>(())
+    })?;
+
+    // NOTE: Builders that are never finished are discarded
+    writer.section("dropped").write_all(b"dropped")?;
+
+    let summary = writer.finish()?;
+    assert_eq!(16, summary.toc().len());
+
+    // NOTE: Spill files are removed
+    assert_eq!(0, std::fs::read_dir(&spill_dir)?.count());
+
+    let reader = Reader::new(&path)?;
+    assert_eq!(summary.content_digest(), reader.content_digest()?);
+
+    // NOTE: Sections are assembled in creation order
+    for (entry, idx) in reader.toc().iter().zip((0..16u8).rev()) {
+        assert_eq!(format!("section{idx:02}").as_bytes(), entry.name());
+        assert_eq!(if idx % 2 == 0 { 10 } else { 5_000 }, entry.len());
+        assert!(entry.checksum().is_some());
+
+        let data = std::fs::read(&path)?;
+        #[allow(clippy::cast_possible_truncation)]
+        let section = &data[entry.pos() as usize..(entry.pos() + entry.len()) as usize];
+        assert!(section.iter().all(|&b| b == idx));
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn parallel_writer_order() -> Result<(), sfa::Error> {
+    let names = |order| -> Result<Vec<String>, sfa::Error> {
+        let writer =
+            ParallelWriter::new(Writer::from_writer(std::io::Cursor::new(vec![]))).use_order(order);
+
+        let b = writer.section("b");
+        let mut c = writer.section("c");
+        let a = writer.section("a");
+
+        c.set_attributes(SectionAttributes::default().with_mode(0o600));
+        c.finish()?;
+        a.finish()?;
+        b.finish()?;
+
+        let (_, summary) = writer.into_inner_with_summary()?;
+
+        assert_eq!(
+            Some(0o600),
+            summary
+                .toc()
+                .section(b"c")
+                .unwrap()
+                .attributes()
+                .unwrap()
+                .mode(),
+        );
+
+        Ok(summary
+            .toc()
+            .iter()
+            .map(|entry| String::from_utf8_lossy(entry.name()).into_owned())
+            .collect())
+    };
+
+    assert_eq!(vec!["b", "c", "a"], names(AssemblyOrder::Created)?);
+    assert_eq!(vec!["c", "a", "b"], names(AssemblyOrder::Finished)?);
+    assert_eq!(vec!["a", "b", "c"], names(AssemblyOrder::Name)?);
+
+    Ok(())
+}
+
+#[test]
+pub fn parallel_writer_order_reproducible() -> Result<(), sfa::Error> {
+    let writer =
+        ParallelWriter::new(Writer::from_writer(std::io::Cursor::new(vec![])).use_reproducible())
+            .use_order(AssemblyOrder::Finished);
+
+    writer.section("a").finish()?;
+
+    assert!(matches!(
+        writer.finish(),
+        Err(sfa::Error::Io(e)) if e.kind() == std::io::ErrorKind::InvalidInput,
+    ));
+
+    // NOTE: Deterministic orders are fine
+    for order in [AssemblyOrder::Created, AssemblyOrder::Name] {
+        let writer = ParallelWriter::new(
+            Writer::from_writer(std::io::Cursor::new(vec![])).use_reproducible(),
+        )
+        .use_order(order);
+
+        writer.section("a").finish()?;
+        writer.finish()?;
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn parallel_writer_options_after_section() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+
+    let writer = ParallelWriter::new(Writer::from_writer(std::io::Cursor::new(vec![])));
+    let mut a = writer.section("a");
+
+    // NOTE: Only applies to sections created afterwards
+    let writer = writer.use_spill_threshold(10).use_spill_dir(dir.path());
+    let mut b = writer.section("b");
+
+    a.write_all(&[1; 100])?;
+    b.write_all(&[2; 100])?;
+    assert_eq!(1, std::fs::read_dir(dir.path())?.count());
+
+    a.finish()?;
+    b.finish()?;
+
+    let (cursor, _) = writer.into_inner_with_summary()?;
+    let path = dir.path().join("archive");
+    std::fs::write(&path, cursor.into_inner())?;
+
+    let reader = Reader::new(&path)?;
+    assert_eq!(2, reader.toc().len());
+    assert_eq!(
+        200,
+        reader.toc().iter().map(|entry| entry.len()).sum::<u64>()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,187 @@
+#![cfg(feature = "parity")]
+
+mod common;
+
+use sfa::{ArchiveEditor, Reader, Writer};
+use std::path::Path;
+
+const BLOCK_SIZE: u32 = 64;
+
+fn write_archive(path: &Path, checksums: bool, parity: bool) -> Result<(), sfa::Error> {
+    let sections = [1_000, 0, 77, 5_000]
+        .into_iter()
+        .enumerate()
+        .map(|(idx, len)| {
+            let data = (0..len).map(|x| (x * (idx + 1)) as u8).collect::<Vec<_>>();
+            (format!("section{idx}"), data)
+        })
+        .collect::<Vec<_>>();
+
+    common::write_archive(path, Some(b"header"), &sections, |mut writer| {
+        if checksums {
+            writer = writer.use_section_checksums();
+        }
+
+        if parity {
+            writer = writer.use_parity(4, 2).use_parity_block_size(BLOCK_SIZE);
+        }
+
+        writer
+    })?;
+
+    Ok(())
+}
+
+/// Flips the byte at the given position of the file.
+fn damage(path: &Path, pos: u64) -> std::io::Result<()> {
+    let mut bytes = std::fs::read(path)?;
+
+    #[allow(clippy::cast_possible_truncation)]
+    let byte = bytes.get_mut(pos as usize).expect("should be in bounds");
+    *byte = !*byte;
+
+    std::fs::write(path, bytes)
+}
+
+/// Flips a byte in the given data block of the file.
+fn damage_block(path: &Path, block: u64) -> std::io::Result<()> {
+    damage(path, block * u64::from(BLOCK_SIZE) + 13)
+}
+
+#[test]
+pub fn parity_repair() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    for checksums in [false, true] {
+        write_archive(&path, checksums, true)?;
+        let expected = std::fs::read(&path)?;
+
+        let reader = Reader::new(&path)?;
+        assert_eq!(4, reader.toc().len());
+        let parity = reader.toc().parity().expect("should have parity");
+
+        // NOTE: Nothing to repair
+        assert_eq!(0, Reader::repair(&path)?);
+
+        // NOTE: Two blocks of one stripe, and one block of another stripe
+        damage_block(&path, 8)?;
+        damage_block(&path, 10)?;
+        damage_block(&path, 13)?;
+
+        // NOTE: Without section checksums, every stripe is checked,
+        // so damaged parity blocks of undamaged sections are repaired as well
+        if !checksums {
+            damage(&path, parity.pos() + parity.len() - 100)?;
+        }
+
+        assert_ne!(expected, std::fs::read(&path)?);
+
+        let repaired = Reader::repair(&path)?;
+        assert_eq!(expected, std::fs::read(&path)?);
+        assert_eq!(if checksums { 3 } else { 4 }, repaired);
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn parity_too_much_damage() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+    write_archive(&path, true, true)?;
+
+    for block in [4, 5, 6] {
+        damage_block(&path, block)?;
+    }
+
+    assert!(matches!(
+        Reader::repair(&path),
+        Err(sfa::Error::Unrepairable { .. }),
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn parity_not_part_of_sections() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+    let plain_path = dir.path().join("plain");
+
+    write_archive(&path, false, true)?;
+    write_archive(&plain_path, false, false)?;
+
+    let reader = Reader::new(&path)?;
+    let plain_reader = Reader::new(&plain_path)?;
+    assert_eq!(&**plain_reader.toc(), &**reader.toc());
+    assert_eq!(plain_reader.content_digest()?, reader.content_digest()?);
+
+    assert!(plain_reader.toc().parity().is_none());
+    assert!(matches!(
+        Reader::repair(&plain_path),
+        Err(sfa::Error::Unrepairable { .. }),
+    ));
+
+    // NOTE: Edited archives keep their parity
+    let mut editor = ArchiveEditor::open(&path)?;
+    editor.remove(b"section0")?;
+    editor.write()?;
+
+    damage_block(&path, 3)?;
+    assert_eq!(1, Reader::repair(&path)?);
+
+    // NOTE: Parity cannot be combined with section frames
+    let mut writer = Writer::from_writer(std::io::Cursor::new(vec![]))
+        .use_parity(4, 2)
+        .use_section_frames();
+    assert!(writer.write_header(b"header").is_err());
+
+    // NOTE: Retrying must not silently drop the parity
+    assert!(writer.write_header(b"header").is_err());
+    assert!(writer.start("a").is_err());
+    assert!(writer.finish().is_err());
+
+    // NOTE: Invalid parity layouts are refused every time as well
+    let mut writer = Writer::from_writer(std::io::Cursor::new(vec![]))
+        .use_parity(4, 2)
+        .use_parity_block_size(0);
+    assert!(writer.start("a").is_err());
+    assert!(writer.start("a").is_err());
+
+    // NOTE: Huge blocks are refused instead of allocating a huge stripe
+    let mut writer = Writer::from_writer(std::io::Cursor::new(vec![]))
+        .use_parity(255, 1)
+        .use_parity_block_size(u32::MAX);
+    assert_eq!(
+        std::io::ErrorKind::InvalidInput,
+        writer.start("a").unwrap_err().kind(),
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn parity_huge_block_size() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    write_archive(&path, false, true)?;
+
+    // NOTE: Forge the block size of the parity metadata
+    let mut bytes = std::fs::read(&path)?;
+    let pos = Reader::new(&path)?.toc().parity().unwrap().pos() as usize;
+    bytes[pos + 4..pos + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+    std::fs::write(&path, bytes)?;
+
+    assert!(matches!(
+        Reader::repair(&path),
+        Err(sfa::Error::Unrepairable { .. }),
+    ));
+    assert!(matches!(
+        ArchiveEditor::open(&path),
+        Err(sfa::Error::Unrepairable { .. }),
+    ));
+
+    Ok(())
+}
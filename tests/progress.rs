@@ -0,0 +1,169 @@
+use sfa::{ExtractOptions, Progress, Reader, TocEntry, Writer};
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Counts {
+    started: Vec<Vec<u8>>,
+    bytes: u64,
+    finished: Vec<(Vec<u8>, u64)>,
+}
+
+#[derive(Default)]
+struct Recorder(Mutex<Counts>);
+
+impl Recorder {
+    fn take(&self) -> Counts {
+        std::mem::take(&mut *self.0.lock().expect("lock is poisoned"))
+    }
+}
+
+impl Progress for Recorder {
+    fn on_section_start(&self, name: &[u8]) {
+        self.0
+            .lock()
+            .expect("lock is poisoned")
+            .started
+            .push(name.to_vec());
+    }
+
+    fn on_bytes(&self, n: u64) {
+        self.0.lock().expect("lock is poisoned").bytes += n;
+    }
+
+    fn on_section_end(&self, entry: &TocEntry) {
+        self.0
+            .lock()
+            .expect("lock is poisoned")
+            .finished
+            .push((entry.name().to_vec(), entry.len()));
+    }
+}
+
+fn expected() -> Counts {
+    Counts {
+        started: vec![b"".to_vec(), b"a".to_vec(), b"dir/b".to_vec()],
+        bytes: 6 + 5 + 100_000,
+        finished: vec![
+            (b"".to_vec(), 6),
+            (b"a".to_vec(), 5),
+            (b"dir/b".to_vec(), 100_000),
+        ],
+    }
+}
+
+#[test]
+pub fn progress_write_verify_extract() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    let recorder = Arc::new(Recorder::default());
+
+    let mut writer = Writer::from_writer(std::fs::File::create(&path)?)
+        .use_section_checksums()
+        .use_progress(recorder.clone());
+
+    writer.write_header(b"header")?;
+    writer.start("a")?;
+    writer.write_all(b"hello")?;
+    writer.write_section_from("dir/b", &mut &vec![7; 100_000][..])?;
+
+    // NOTE: The last section is finished when finishing the archive
+    assert_eq!(
+        2,
+        recorder.0.lock().expect("lock is poisoned").finished.len()
+    );
+
+    writer.finish()?;
+    assert_eq!(expected(), recorder.take());
+
+    let reader = Reader::new(&path)?.use_progress(recorder.clone());
+
+    reader.content_digest()?;
+    assert_eq!(expected(), recorder.take());
+
+    #[cfg(feature = "rayon")]
+    {
+        assert!(reader.par_verify().is_ok());
+
+        // NOTE: Sections are verified concurrently
+        let mut counts = recorder.take();
+        counts.started.sort();
+        counts.finished.sort();
+        assert_eq!(expected(), counts);
+    }
+
+    assert_eq!(
+        2,
+        reader.extract_to(dir.path().join("out"), &ExtractOptions::default())?
+    );
+
+    let mut expected = expected();
+    expected.started.remove(0);
+    expected.finished.remove(0);
+    expected.bytes -= 6;
+    assert_eq!(expected, recorder.take());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "signing")]
+pub fn progress_verify_signature() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    let signing_key = sfa::SigningKey::from_bytes(&[7; 32]);
+
+    let mut writer = Writer::from_writer(std::fs::File::create(&path)?);
+    writer.write_header(b"header")?;
+    writer.start("a")?;
+    writer.write_all(b"hello")?;
+    writer.start("dir/b")?;
+    writer.write_all(&vec![7; 100_000])?;
+    writer.finish_signed(&signing_key)?;
+
+    let recorder = Arc::new(Recorder::default());
+    let reader = Reader::new(&path)?.use_progress(recorder.clone());
+
+    reader.verify_signature(&signing_key.verifying_key())?;
+    assert_eq!(expected(), recorder.take());
+
+    Ok(())
+}
+
+#[test]
+pub fn progress_implicit_section() -> Result<(), sfa::Error> {
+    let recorder = Arc::new(Recorder::default());
+
+    let mut writer =
+        Writer::from_writer(std::io::Cursor::new(vec![])).use_progress(recorder.clone());
+
+    // NOTE: Empty writes do not start the implicit section
+    writer.write_all(b"")?;
+    assert!(recorder
+        .0
+        .lock()
+        .expect("lock is poisoned")
+        .started
+        .is_empty());
+
+    writer.write_all(b"hello")?;
+    writer.write_all(b" world")?;
+    writer.start("a")?;
+    writer.write_all(b"abc")?;
+    writer.finish()?;
+
+    assert_eq!(
+        Counts {
+            started: vec![b"".to_vec(), b"a".to_vec()],
+            bytes: 11 + 3,
+            finished: vec![(b"".to_vec(), 11), (b"a".to_vec(), 3)],
+        },
+        recorder.take()
+    );
+
+    Ok(())
+}
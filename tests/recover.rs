@@ -0,0 +1,149 @@
+use sfa::{Reader, SectionAttributes, Writer};
+use std::{
+    fs::File,
+    io::{Cursor, Read, Write},
+    path::Path,
+};
+
+const SECTIONS: &[(&str, &[u8])] = &[("a", b"hello"), ("b/c", b""), ("d", b"world!")];
+
+fn write_framed(finish: bool) -> Result<Vec<u8>, sfa::Error> {
+    let mut writer = Writer::from_writer(Cursor::new(vec![]))
+        .use_section_frames()
+        .use_leading_magic(*b"RECOVER!");
+
+    writer.write_header(b"header")?;
+
+    for (name, data) in SECTIONS {
+        writer.start(*name)?;
+        writer.write_all(data)?;
+        writer.set_attributes(SectionAttributes::default().with_mode(0o644))?;
+    }
+
+    if finish {
+        return Ok(writer.into_inner()?.into_inner());
+    }
+
+    writer.start("unfinished")?;
+    writer.write_all(b"lost")?;
+    Ok(writer.get_mut().get_ref().clone())
+}
+
+/// Checks that the archive contains a prefix of the expected sections,
+/// and returns the number of sections.
+fn check_sections(path: &Path) -> Result<usize, sfa::Error> {
+    let reader = Reader::new(path)?;
+    assert!(reader.toc().len() <= SECTIONS.len());
+
+    for (entry, (name, expected)) in reader.toc().iter().zip(SECTIONS) {
+        assert_eq!(name.as_bytes(), entry.name());
+        assert_eq!(Some(0o644), entry.attributes().and_then(|x| x.mode()));
+
+        let mut data = vec![];
+        entry.reader(path)?.read_to_end(&mut data)?;
+        assert_eq!(expected, &data);
+    }
+
+    Ok(reader.toc().len())
+}
+
+#[test]
+pub fn recover_framed_archive_readable() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+    std::fs::write(&path, write_framed(true)?)?;
+
+    assert_eq!(SECTIONS.len(), check_sections(&path)?);
+
+    // NOTE: Intact archives are left untouched
+    let bytes = std::fs::read(&path)?;
+    assert!(sfa::recover(&path)?.is_none());
+    assert_eq!(bytes, std::fs::read(&path)?);
+
+    Ok(())
+}
+
+#[test]
+pub fn recover_unfinished() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+    std::fs::write(&path, write_framed(false)?)?;
+
+    assert!(Reader::new(&path).is_err());
+
+    let summary = sfa::recover(&path)?.expect("archive should be recovered");
+    assert_eq!(SECTIONS.len(), summary.toc().len());
+
+    let reader = Reader::new(&path)?;
+    assert_eq!(summary.content_digest(), reader.content_digest()?);
+
+    let header = reader.header().expect("header should be recovered");
+    let mut data = vec![];
+    header.reader(&path)?.read_to_end(&mut data)?;
+    assert_eq!(b"header", &*data);
+
+    assert_eq!(
+        Some(sfa::ArchiveKind::Tagged {
+            app_id: sfa::AppId::from(*b"RECOVER!")
+        }),
+        sfa::detect(File::open(&path)?)?,
+    );
+
+    assert_eq!(SECTIONS.len(), check_sections(&path)?);
+
+    Ok(())
+}
+
+#[test]
+pub fn recover_truncated_anywhere() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    let bytes = write_framed(true)?;
+    let mut recovered_counts = vec![];
+
+    for len in 0..bytes.len() {
+        std::fs::write(&path, bytes.get(..len).expect("should be in bounds"))?;
+
+        match sfa::recover(&path) {
+            Ok(Some(summary)) => {
+                let count = check_sections(&path)?;
+                assert_eq!(summary.toc().len(), count);
+                recovered_counts.push(count);
+            }
+            Ok(None) => panic!("truncated archive at {len} bytes should not be intact"),
+            Err(sfa::Error::NoSectionFrames { .. }) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    // NOTE: Every complete section is recovered eventually
+    assert_eq!(Some(&SECTIONS.len()), recovered_counts.iter().max());
+    assert!(recovered_counts.contains(&0));
+
+    Ok(())
+}
+
+#[test]
+pub fn recover_unframed() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    let mut writer = Writer::from_writer(Cursor::new(vec![]));
+    writer.start("a")?;
+    writer.write_all(b"hello")?;
+    let bytes = writer.get_mut().get_ref().clone();
+    std::fs::write(&path, &bytes)?;
+
+    assert!(matches!(
+        sfa::recover(&path),
+        Err(sfa::Error::NoSectionFrames { .. }),
+    ));
+    assert_eq!(bytes, std::fs::read(&path)?);
+
+    // NOTE: Framed archives do not have an implicit unnamed section
+    let mut writer = Writer::from_writer(Cursor::new(vec![])).use_section_frames();
+    assert!(writer.write_all(b"hello").is_err());
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use sfa::{ArchiveEditor, Reader, TocCopy, Writer};
+use std::{
+    io::{Cursor, Write},
+    path::Path,
+};
+
+fn write_archive(redundant: bool) -> Result<(Vec<u8>, u64), sfa::Error> {
+    let mut writer = Writer::from_writer(Cursor::new(vec![]))
+        .use_app_id(*b"TEST", 1)
+        .use_compact_toc();
+
+    if redundant {
+        writer = writer.use_redundant_toc();
+    }
+
+    writer.write_header(b"header")?;
+    writer.start("a")?;
+    writer.write_all(b"hello")?;
+    writer.start("b")?;
+    writer.write_all(b"world")?;
+
+    let (cursor, summary) = writer.into_inner_with_summary()?;
+    let data_end = summary
+        .toc()
+        .iter()
+        .map(|entry| entry.pos() + entry.len())
+        .max()
+        .unwrap_or_default();
+
+    Ok((cursor.into_inner(), data_end))
+}
+
+fn corrupt(path: &Path, bytes: &[u8], pos: usize) -> std::io::Result<()> {
+    let mut bytes = bytes.to_vec();
+    let byte = bytes.get_mut(pos).expect("should be in bounds");
+    *byte = !*byte;
+    std::fs::write(path, bytes)
+}
+
+#[test]
+pub fn redundant_toc_survives_any_corrupted_byte() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    let (bytes, data_end) = write_archive(true)?;
+    std::fs::write(&path, &bytes)?;
+
+    let expected = Reader::new(&path)?;
+    assert_eq!(TocCopy::Primary, expected.toc_copy());
+    assert_eq!(2, expected.toc().len());
+
+    let mut used_backup = false;
+
+    #[allow(clippy::cast_possible_truncation)]
+    for pos in data_end as usize..bytes.len() {
+        corrupt(&path, &bytes, pos)?;
+
+        let reader = Reader::new(&path)?;
+        assert_eq!(
+            &**expected.toc(),
+            &**reader.toc(),
+            "corrupted byte at {pos}"
+        );
+        assert_eq!(expected.header(), reader.header());
+        assert_eq!(expected.app_id(), reader.app_id());
+        assert_eq!(expected.app_version(), reader.app_version());
+
+        used_backup |= reader.toc_copy() == TocCopy::Backup;
+    }
+
+    assert!(used_backup);
+
+    Ok(())
+}
+
+#[test]
+pub fn redundant_toc_not_used_by_default() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    let (bytes, _) = write_archive(false)?;
+    let (redundant_bytes, _) = write_archive(true)?;
+    assert!(bytes.len() < redundant_bytes.len());
+
+    corrupt(&path, &bytes, bytes.len() - 1)?;
+    assert!(Reader::new(&path).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn redundant_toc_kept_by_editor() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    let (bytes, _) = write_archive(true)?;
+    std::fs::write(&path, &bytes)?;
+
+    let mut editor = ArchiveEditor::open(&path)?;
+    editor.remove(b"a")?;
+    editor.write()?;
+
+    let bytes = std::fs::read(&path)?;
+    corrupt(&path, &bytes, bytes.len() - 1)?;
+
+    let reader = Reader::new(&path)?;
+    assert_eq!(TocCopy::Backup, reader.toc_copy());
+    assert_eq!(1, reader.toc().len());
+    assert_eq!(b"b", reader.toc()[0].name());
+
+    Ok(())
+}
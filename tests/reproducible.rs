@@ -0,0 +1,192 @@
+use sfa::{PackOptions, SectionAttributes, Writer};
+use std::{
+    fs::File,
+    io::{Cursor, Write},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+fn hash(bytes: &[u8]) -> u128 {
+    xxhash_rust::xxh3::xxh3_128(bytes)
+}
+
+fn create_tree(root: &Path, mtime: SystemTime) -> std::io::Result<()> {
+    std::fs::create_dir_all(root.join("b/c"))?;
+
+    for (name, data) in [("a", "a"), ("b/c/d", "d"), ("b/e", "e"), ("f", "")] {
+        let path = root.join(name);
+        std::fs::write(&path, data)?;
+
+        let file = File::options().write(true).open(&path)?;
+        file.set_modified(mtime)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o644))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn pack(root: &Path, reproducible: bool) -> Result<Vec<u8>, sfa::Error> {
+    let mut writer = Writer::from_writer(Cursor::new(vec![]))
+        .use_leading_magic(*b"REPRODUC")
+        .use_app_id(*b"REPR", 1)
+        .use_section_checksums()
+        .use_compact_toc();
+
+    if reproducible {
+        writer = writer.use_reproducible();
+    }
+
+    writer.write_header(b"header")?;
+    writer.add_dir_recursive(
+        root,
+        &PackOptions::default().record_mode(true).record_mtime(true),
+    )?;
+
+    Ok(writer.into_inner()?.into_inner())
+}
+
+#[test]
+pub fn reproducible_pack_across_runs() -> Result<(), sfa::Error> {
+    let mut outputs = vec![];
+
+    for secs in [1_000_000_000, 1_500_000_000] {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path().join("tree");
+        create_tree(&root, SystemTime::UNIX_EPOCH + Duration::from_secs(secs))?;
+
+        outputs.push((pack(&root, true)?, pack(&root, false)?));
+    }
+
+    let [(reproducible_a, regular_a), (reproducible_b, regular_b)] = &outputs[..] else {
+        unreachable!();
+    };
+
+    assert_eq!(hash(reproducible_a), hash(reproducible_b));
+    assert_eq!(reproducible_a, reproducible_b);
+
+    // NOTE: Without reproducible mode, the modification times end up in the archive
+    assert_ne!(hash(regular_a), hash(regular_b));
+
+    Ok(())
+}
+
+#[test]
+pub fn reproducible_set_attributes_drops_mtime() -> Result<(), sfa::Error> {
+    let build = |mtime: SystemTime| -> Result<Vec<u8>, sfa::Error> {
+        let mut writer = Writer::from_writer(Cursor::new(vec![])).use_reproducible();
+        writer.start("a")?;
+        writer.write_all(b"hello")?;
+        writer.set_attributes(SectionAttributes::default().with_mtime(mtime))?;
+        writer.start("b")?;
+        writer.write_all(b"world")?;
+        writer.set_attributes(
+            SectionAttributes::default()
+                .with_mode(0o755)
+                .with_mtime(mtime),
+        )?;
+        Ok(writer.into_inner()?.into_inner())
+    };
+
+    let a = build(SystemTime::UNIX_EPOCH)?;
+    let b = build(SystemTime::now())?;
+    assert_eq!(hash(&a), hash(&b));
+
+    let reader = sfa::Reader::from_reader(&mut Cursor::new(&a))?;
+
+    // NOTE: Attributes without a modification time are dropped entirely
+    assert!(reader.toc().section(b"a").unwrap().attributes().is_none());
+
+    let attributes = reader.toc().section(b"b").unwrap().attributes().unwrap();
+    assert_eq!(Some(0o755), attributes.mode());
+    assert_eq!(None, attributes.mtime());
+
+    Ok(())
+}
+
+#[test]
+pub fn reproducible_stable_format() -> Result<(), sfa::Error> {
+    // NOTE: Pins the exact output, so format changes that would break
+    // content-addressed caches are noticed
+    let mut writer = Writer::from_writer(Cursor::new(vec![])).use_reproducible();
+    writer.start("Verse 1")?;
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.start("Verse 2")?;
+    writer.write_all(b"Lazy ways, a lazy smile\n")?;
+    let bytes = writer.into_inner()?.into_inner();
+
+    assert_eq!(GOLDEN_V1, hash(&bytes));
+
+    let mut writer = Writer::from_writer(Cursor::new(vec![]))
+        .use_reproducible()
+        .use_section_checksums()
+        .use_compact_toc();
+    writer.write_header(b"header")?;
+    writer.start("Verse 1")?;
+    writer.write_all(b"Glazed eyes and cherry pie\n")?;
+    writer.set_attributes(SectionAttributes::default().with_mode(0o644))?;
+    let bytes = writer.into_inner()?.into_inner();
+
+    assert_eq!(GOLDEN_V2, hash(&bytes));
+
+    Ok(())
+}
+
+const GOLDEN_V1: u128 = 0xf9cd09838c986f1f78814a980e5eec8c;
+const GOLDEN_V2: u128 = 0xd6aa43de590653aad4d5c28eb037de2e;
+
+#[test]
+#[cfg(feature = "encryption")]
+pub fn reproducible_refuses_encryption() {
+    let key = sfa::EncryptionKey::new(1, [7; 32]);
+
+    let mut writer = Writer::from_writer(Cursor::new(vec![]))
+        .use_reproducible()
+        .use_encryption(key);
+
+    assert_eq!(
+        std::io::ErrorKind::InvalidInput,
+        writer.start("a").unwrap_err().kind(),
+    );
+
+    // NOTE: Retrying must not write the section unencrypted
+    assert!(writer.start("a").is_err());
+    assert!(writer.write_all(b"hello").is_err());
+    assert!(writer.finish().is_err());
+}
+
+#[test]
+#[cfg(feature = "encryption")]
+pub fn reproducible_refuses_encryption_after_start() -> Result<(), sfa::Error> {
+    let mut writer = Writer::from_writer(Cursor::new(vec![])).use_reproducible();
+    writer.start("a")?;
+    writer.write_all(b"hello")?;
+
+    // NOTE: Encryption can be enabled after writing data, which must still be refused
+    let mut writer = writer.use_encryption(sfa::EncryptionKey::new(1, [7; 32]));
+
+    assert_eq!(
+        std::io::ErrorKind::InvalidInput,
+        writer.start("b").unwrap_err().kind(),
+    );
+    assert!(writer.write_all(b"world").is_err());
+    assert!(writer.finish().is_err());
+
+    // NOTE: Reproducible mode cannot be enabled after writing data, as modification times
+    // of earlier sections may have been recorded
+    let mut writer = Writer::from_writer(Cursor::new(vec![]));
+    writer.start("a")?;
+
+    let mut writer = writer
+        .use_reproducible()
+        .use_encryption(sfa::EncryptionKey::new(1, [7; 32]));
+    writer.start("b")?;
+    writer.write_all(b"world")?;
+    writer.finish()?;
+
+    Ok(())
+}
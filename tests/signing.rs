@@ -0,0 +1,118 @@
+#![cfg(feature = "signing")]
+
+mod common;
+
+use common::write_sections;
+use sfa::{Reader, SigningKey, Writer};
+use std::{
+    fs::File,
+    io::{Seek, SeekFrom, Write},
+    path::Path,
+};
+
+fn write_archive(path: &Path, key: Option<&SigningKey>) -> Result<(), sfa::Error> {
+    let mut file = File::create(path)?;
+    let mut writer = Writer::from_writer(&mut file).use_leading_magic(*b"SEGMENT!");
+
+    let sections: [(&str, &[u8]); 2] = [
+        ("Verse 1", b"Glazed eyes and cherry pie\n"),
+        ("Verse 2", b"Lazy ways, a lazy smile\n"),
+    ];
+    write_sections(&mut writer, Some(b"header"), &sections)?;
+
+    match key {
+        Some(key) => writer.finish_signed(key)?,
+        None => writer.finish()?,
+    };
+
+    file.sync_all()?;
+    Ok(())
+}
+
+#[test]
+pub fn signing_valid() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let key = SigningKey::from_bytes(&[7; 32]);
+    write_archive(&path, Some(&key))?;
+
+    // NOTE: The signature does not affect regular reading
+    let reader = Reader::new(&path)?;
+    assert_eq!(2, reader.toc().len());
+    assert_eq!(b"header".len() as u64, reader.header().unwrap().len());
+
+    reader.verify_signature(&key.verifying_key())?;
+
+    let reader = Reader::from_reader(&mut File::open(&path)?)?;
+    reader.verify_signature_with_reader(&mut File::open(&path)?, &key.verifying_key())?;
+
+    Ok(())
+}
+
+#[test]
+pub fn signing_wrong_key() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    write_archive(&path, Some(&SigningKey::from_bytes(&[7; 32])))?;
+
+    let reader = Reader::new(&path)?;
+    let other = SigningKey::from_bytes(&[8; 32]);
+
+    assert!(matches!(
+        reader.verify_signature(&other.verifying_key()),
+        Err(sfa::Error::InvalidSignature { .. }),
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn signing_unsigned() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    write_archive(&path, None)?;
+
+    let reader = Reader::new(&path)?;
+    let key = SigningKey::from_bytes(&[7; 32]);
+
+    assert!(matches!(
+        reader.verify_signature(&key.verifying_key()),
+        Err(sfa::Error::MissingSignature { .. }),
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn signing_modified_section() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("cherry_pie");
+
+    let key = SigningKey::from_bytes(&[7; 32]);
+    write_archive(&path, Some(&key))?;
+
+    let reader = Reader::new(&path)?;
+    let pos = reader.toc().section(b"Verse 2").unwrap().pos();
+
+    {
+        let mut file = File::options().write(true).open(&path)?;
+        file.seek(SeekFrom::Start(pos))?;
+        file.write_all(b"Crazy")?;
+        file.sync_all()?;
+    }
+
+    // NOTE: Section contents are not covered by the table of contents checksum
+    let reader = Reader::new(&path)?;
+
+    match reader.verify_signature(&key.verifying_key()) {
+        Err(sfa::Error::InvalidSignature { context }) => {
+            assert_eq!(Some(&b"Verse 2"[..]), context.section_name());
+        }
+        other => panic!("expected invalid signature, got {other:?}"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use sfa::{Reader, Writer};
+use std::{
+    fs::File,
+    io::{Cursor, Write},
+    path::Path,
+};
+
+fn write_archive(path: &Path, data: &[u8]) -> Result<sfa::WriteSummary, sfa::Error> {
+    let sections: [(&str, &[u8]); 2] = [("a", data), ("b/c", b"world")];
+    common::write_archive(path, Some(b"header"), &sections, |writer| {
+        writer.use_app_id(*b"TEST", 3)
+    })
+}
+
+#[test]
+pub fn summary_open_reader() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    let summary = write_archive(&path, b"hello")?;
+    let expected = Reader::new(&path)?;
+
+    for reader in [
+        Reader::from_summary(summary.clone()),
+        Reader::from_summary_checked(&mut File::open(&path)?, summary.clone())?,
+        Reader::open_with_summary(&path, summary.clone())?,
+    ] {
+        assert_eq!(&**expected.toc(), &**reader.toc());
+        assert_eq!(expected.header(), reader.header());
+        assert_eq!(expected.app_id(), reader.app_id());
+        assert_eq!(Some(3), reader.app_version());
+    }
+
+    // NOTE: Opening with a path allows operations that read the file
+    let reader = Reader::open_with_summary(&path, summary.clone())?;
+    assert_eq!(summary.content_digest(), reader.content_digest()?);
+
+    Ok(())
+}
+
+#[test]
+pub fn summary_mismatch() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+
+    let summary = write_archive(&path, b"hello")?;
+
+    // NOTE: Same layout, but different data
+    write_archive(&path, b"jello")?;
+    let reader = Reader::open_with_summary(&path, summary.clone())?;
+    assert_eq!(&**Reader::new(&path)?.toc(), &**reader.toc());
+
+    // NOTE: Different table of contents position
+    write_archive(&path, b"hello world")?;
+    assert!(matches!(
+        Reader::open_with_summary(&path, summary.clone()),
+        Err(sfa::Error::SummaryMismatch { .. }),
+    ));
+
+    // NOTE: Different archive altogether
+    let mut writer = Writer::from_writer(Cursor::new(vec![]));
+    writer.start("a")?;
+    writer.write_all(b"hello")?;
+    let mut other = Cursor::new(writer.into_inner()?.into_inner());
+
+    assert!(matches!(
+        Reader::from_summary_checked(&mut other, summary),
+        Err(sfa::Error::SummaryMismatch { .. }),
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use sfa::{Reader, Writer};
+use std::io::{Cursor, IoSlice, Seek, SeekFrom, Write};
+
+/// Counts the seeks (including stream position queries) of the inner writer
+struct CountingWriter {
+    inner: Cursor<Vec<u8>>,
+    seeks: usize,
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl Seek for CountingWriter {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.seeks += 1;
+        self.inner.seek(pos)
+    }
+}
+
+fn write_archive(
+    buffer: usize,
+    vectored: bool,
+    frames: bool,
+) -> Result<CountingWriter, sfa::Error> {
+    let mut writer = Writer::from_writer(CountingWriter {
+        inner: Cursor::new(vec![]),
+        seeks: 0,
+    })
+    .use_write_buffer(buffer)
+    .use_section_checksums();
+
+    if frames {
+        writer = writer.use_section_frames();
+    }
+
+    writer.write_header(b"header")?;
+
+    for idx in 0..100 {
+        writer.start(format!("section{idx}"))?;
+
+        let blocks = [b"block".repeat(idx), vec![idx as u8; 3], b"x".repeat(1_000)];
+
+        if vectored {
+            let mut bufs = blocks.iter().map(|x| IoSlice::new(x)).collect::<Vec<_>>();
+            let mut bufs = &mut bufs[..];
+
+            while !bufs.is_empty() {
+                let n = writer.write_vectored(bufs)?;
+                IoSlice::advance_slices(&mut bufs, n);
+            }
+        } else {
+            for block in &blocks {
+                writer.write_all(block)?;
+            }
+        }
+    }
+
+    writer.into_inner()
+}
+
+#[test]
+pub fn write_buffer_same_output() -> Result<(), sfa::Error> {
+    for frames in [false, true] {
+        let expected = write_archive(0, false, frames)?.inner.into_inner();
+
+        for (buffer, vectored) in [(0, true), (64, false), (64, true), (64 * 1_024, true)] {
+            let bytes = write_archive(buffer, vectored, frames)?.inner.into_inner();
+            assert_eq!(expected, bytes, "buffer={buffer}, vectored={vectored}");
+        }
+
+        let reader = Reader::from_reader(&mut Cursor::new(expected))?;
+        assert_eq!(100, reader.toc().len());
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn write_buffer_tracks_position() -> Result<(), sfa::Error> {
+    // NOTE: The position is only queried once, unless the writer seeks back to patch frames
+    assert_eq!(1, write_archive(0, false, false)?.seeks);
+    assert_eq!(1, write_archive(64 * 1_024, true, false)?.seeks);
+    assert_eq!(1 + 2 * 101, write_archive(64 * 1_024, true, true)?.seeks);
+
+    Ok(())
+}
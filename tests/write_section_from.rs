@@ -0,0 +1,119 @@
+use sfa::{Reader, Writer};
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+};
+
+#[test]
+pub fn write_section_from_reader_and_file() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("archive");
+    let src_path = dir.path().join("src");
+
+    let big = (0..1_000_000)
+        .map(|x: u32| (x % 253) as u8)
+        .collect::<Vec<_>>();
+    std::fs::write(&src_path, &big)?;
+
+    let mut writer = Writer::from_writer(File::create(&path)?).use_section_checksums();
+
+    assert_eq!(5, writer.write_section_from("reader", &mut &b"hello"[..])?);
+    assert_eq!(
+        0,
+        writer.write_section_from("empty", &mut std::io::empty())?
+    );
+
+    let mut src = File::open(&src_path)?;
+    assert_eq!(
+        big.len() as u64,
+        writer.write_section_from_file("file", &src)?
+    );
+
+    // NOTE: Files are read from their current position
+    src.seek(SeekFrom::Start(999_990))?;
+    assert_eq!(10, writer.write_section_from_file("tail", &src)?);
+    assert_eq!(0, writer.write_section_from_file("end", &src)?);
+
+    writer.finish()?;
+
+    let reader = Reader::new(&path)?;
+    let read = |name: &[u8]| -> std::io::Result<Vec<u8>> {
+        let mut data = vec![];
+        reader
+            .toc()
+            .section(name)
+            .expect("section should exist")
+            .reader(&path)?
+            .read_to_end(&mut data)?;
+        Ok(data)
+    };
+
+    assert_eq!(b"hello", &*read(b"reader")?);
+    assert!(read(b"empty")?.is_empty());
+    assert_eq!(big, read(b"file")?);
+    assert_eq!(big.get(999_990..), Some(&*read(b"tail")?));
+    assert!(read(b"end")?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+pub fn write_section_from_invalid_name() -> Result<(), sfa::Error> {
+    let mut writer = Writer::from_writer(std::io::Cursor::new(vec![])).use_path_validation();
+
+    assert!(writer
+        .write_section_from("../escape", &mut &b"hello"[..])
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn write_section_from_file_kernel_copy() -> Result<(), sfa::Error> {
+    let dir = tempfile::tempdir()?;
+    let src_path = dir.path().join("src");
+
+    let big = (0..1_000_000)
+        .map(|x: u32| (x % 253) as u8)
+        .collect::<Vec<_>>();
+    std::fs::write(&src_path, &big)?;
+
+    let write = |path: &std::path::Path, kernel_copy: bool| -> Result<(), sfa::Error> {
+        let mut writer = Writer::from_writer(File::create(path)?)
+            .use_section_checksums()
+            .use_write_buffer(4_096);
+
+        if kernel_copy {
+            writer = writer.use_kernel_copy();
+        }
+
+        // NOTE: Buffered data is flushed before copying
+        writer.write_header(b"header")?;
+        writer.write_section_from("small", &mut &b"hello"[..])?;
+
+        let mut src = File::open(&src_path)?;
+        assert_eq!(
+            big.len() as u64,
+            writer.write_section_from_file("file", &src)?
+        );
+
+        src.seek(SeekFrom::Start(999_990))?;
+        assert_eq!(10, writer.write_section_from_file("tail", &src)?);
+        assert_eq!(0, writer.write_section_from_file("end", &src)?);
+
+        writer.finish()?;
+        Ok(())
+    };
+
+    let path = dir.path().join("archive");
+    let plain_path = dir.path().join("plain");
+    write(&path, true)?;
+    write(&plain_path, false)?;
+
+    assert_eq!(std::fs::read(&plain_path)?, std::fs::read(&path)?);
+
+    #[cfg(feature = "rayon")]
+    assert!(Reader::new(&path)?.par_verify().is_ok());
+
+    Ok(())
+}